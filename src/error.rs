@@ -9,6 +9,36 @@ pub enum GameError {
 
   #[error("Account Is Not Writable")]
   NotWritable,
+
+  #[error("Hit Report Does Not Match Board Commitment")]
+  FalseHitReport,
+
+  #[error("Ship Axis Must Be 1 Or 2")]
+  InvalidShipAxis,
+
+  #[error("Ship Is Missing From Revealed Board")]
+  MissingShip,
+
+  #[error("Revealed Fleet Does Not Match Game Fleet")]
+  FleetMismatch,
+
+  #[error("Ship Is Out Of Board Bounds")]
+  ShipOutOfBounds,
+
+  #[error("Ships Overlap")]
+  OverlappingShips,
+
+  #[error("Ships Touch Each Other")]
+  TouchingShips,
+
+  #[error("Ship Does Not Match Its Commitment")]
+  FalseShipReveal,
+
+  #[error("Ship Is Not Sunk")]
+  ShipNotSunk,
+
+  #[error("Invalid Game Status Transition")]
+  InvalidTransition,
 }
 
 impl From<GameError> for ProgramError {
@@ -1,5 +1,5 @@
 use crate::error::GameError::InvalidInstruction;
-use crate::state::{Init,Join,Move,ClaimVictory,Tournament,CounterFinder,FinderFinder,InitTournamentCounter};
+use crate::state::{Init,RegisterHost,RegisterReferrer,FinalReport,Special,FfaInit,FfaJoin,FfaShot,FfaClaim,Join,Rematch,SeriesGame,Move,Salvo,SunkShip,ClaimVictory,Tournament,CounterFinder,FinderFinder,InitTournamentCounter,ProgramConfig};
 use borsh::BorshDeserialize;
 use solana_program::program_error::ProgramError;
 
@@ -25,6 +25,33 @@ pub enum GameInstruction {
   UpdateFinderFinder{ f_finder : FinderFinder},
   DeleteAuth,
   Delete,
+  PlaySalvo {salvo : Salvo},
+  AnnounceSunk {sunk : SunkShip},
+  UpdateConfig{ config : ProgramConfig},
+  ClaimvictoryFlagFall,
+  Resign,
+  OfferDraw,
+  AcceptDraw,
+  CloseGame,
+  ProposeRematch{ rematch : Rematch},
+  AcceptRematch{ rematch : Rematch},
+  CancelRematch,
+  CommitSeriesGame{ game : SeriesGame},
+  InitFfa{ init : FfaInit},
+  JoinFfa{ join : FfaJoin},
+  PlayFfa{ shot : FfaShot},
+  ClaimFfaVictory,
+  ClaimFfaTimeout,
+  AbortFfa,
+  PlaySpecial {special : Special},
+  ClaimRevealTimeout,
+  Finalize {report : FinalReport},
+  ExpireGame,
+  RegisterHost{ host : RegisterHost},
+  RegisterReferrer{ referrer : RegisterReferrer},
+  WithdrawReferralEarnings,
+  RevealFfa{ claim : FfaClaim},
+  TournamentFinalize {report : FinalReport},
 }
 
 impl GameInstruction {
@@ -75,6 +102,63 @@ impl GameInstruction {
       },
       17 => Self::DeleteAuth,
       18 => Self::Delete,
+      19 => Self::PlaySalvo{
+        salvo: Salvo::try_from_slice(rest)?,
+      },
+      20 => Self::AnnounceSunk{
+        sunk: SunkShip::try_from_slice(rest)?,
+      },
+      21 => Self::UpdateConfig{
+        config: ProgramConfig::try_from_slice(rest)?,
+      },
+      22 => Self::ClaimvictoryFlagFall,
+      23 => Self::Resign,
+      24 => Self::OfferDraw,
+      25 => Self::AcceptDraw,
+      26 => Self::CloseGame,
+      27 => Self::ProposeRematch{
+        rematch: Rematch::try_from_slice(rest)?,
+      },
+      28 => Self::AcceptRematch{
+        rematch: Rematch::try_from_slice(rest)?,
+      },
+      29 => Self::CancelRematch,
+      30 => Self::CommitSeriesGame{
+        game: SeriesGame::try_from_slice(rest)?,
+      },
+      31 => Self::InitFfa{
+        init: FfaInit::try_from_slice(rest)?,
+      },
+      32 => Self::JoinFfa{
+        join: FfaJoin::try_from_slice(rest)?,
+      },
+      33 => Self::PlayFfa{
+        shot: FfaShot::try_from_slice(rest)?,
+      },
+      34 => Self::ClaimFfaVictory,
+      35 => Self::ClaimFfaTimeout,
+      36 => Self::AbortFfa,
+      37 => Self::PlaySpecial{
+        special: Special::try_from_slice(rest)?,
+      },
+      38 => Self::ClaimRevealTimeout,
+      39 => Self::Finalize{
+        report: FinalReport::try_from_slice(rest)?,
+      },
+      40 => Self::ExpireGame,
+      41 => Self::RegisterHost{
+        host: RegisterHost::try_from_slice(rest)?,
+      },
+      42 => Self::RegisterReferrer{
+        referrer: RegisterReferrer::try_from_slice(rest)?,
+      },
+      43 => Self::WithdrawReferralEarnings,
+      44 => Self::RevealFfa{
+        claim: FfaClaim::try_from_slice(rest)?,
+      },
+      45 => Self::TournamentFinalize{
+        report: FinalReport::try_from_slice(rest)?,
+      },
 
       _ => return Err(InvalidInstruction.into()),
     })
@@ -1,5 +1,10 @@
 use crate::instruction::GameInstruction;
-use crate::state::{ClaimVictory, CounterFinder, FinderFinder, GameState, Init, InitTournamentCounter, Join, Move, TGameState, Tournament, TournamentAccount, TournamentCounter
+use crate::error::GameError::{FalseHitReport, InvalidShipAxis, MissingShip, FleetMismatch, ShipOutOfBounds, OverlappingShips, TouchingShips,
+  FalseShipReveal, ShipNotSunk, InvalidTransition};
+use crate::state::{ClaimVictory, FinalReport, CounterFinder, FfaClaim, FfaGameState, FfaInit, FfaJoin, FfaShot, FinderFinder, Fleet, GameResult, GameState, GameStatus, HostProfile, Init, InitTournamentCounter, Join, Move, MoveLog, MoveRecord, ProgramConfig, ReferrerProfile, RegisterHost, RegisterReferrer, Rematch, Salvo, SeriesGame, ShipPlacement, SpecialRecord, SunkShip, TGameState, Tournament, TournamentAccount, TournamentCounter,
+  BOARD_TREE_DEPTH, MAX_PROVEN_REPORTS, CLASSIC_BOARD_SIZE, CLASSIC_FLEET, FFA_PENDING_CAPACITY, MAX_BOARD_SIZE, MAX_FFA_PLAYERS, MAX_FLEET_SHIPS, MAX_SPECIAL_WEAPONS, MIN_BOARD_SIZE, MIN_FFA_PLAYERS,
+  EXPIRY_CRANK_BOUNTY, RESULT_ABORT, RESULT_DRAW_AGREED, RESULT_EXPIRED, RESULT_FALSE_REPORT, RESULT_FLAG_FALL, RESULT_RESIGN, RESULT_REVEAL_TIMEOUT, RESULT_SCORE, RESULT_SERIES_FORFEIT, RESULT_SERIES_SCORE, RESULT_TIME,
+  WEAPON_AIRSTRIKE, WEAPON_RADAR, WEAPON_SHOT, WEAPON_TORPEDO
 };
 
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -7,17 +12,29 @@ use std::str::FromStr;
 use solana_program::{
   account_info::{next_account_info, AccountInfo},
   entrypoint::ProgramResult,
+  program_error::ProgramError,
   pubkey::Pubkey,
   sysvar::{clock::Clock, Sysvar,},
   keccak,
   rent::Rent,
   system_instruction,
-  program::invoke_signed,
+  program::{invoke, invoke_signed},
+  program_pack::Pack,
+  msg,
   system_program::ID,
 };
 
 
 
+//accounts a finished game pays out through, remaining holds the host profile, escrow and referrers
+struct SettleAccounts<'b, 'a> {
+  game_state: &'b AccountInfo<'a>,
+  initializer: &'b AccountInfo<'a>,
+  guest: &'b AccountInfo<'a>,
+  host: &'b AccountInfo<'a>,
+  remaining: &'b [AccountInfo<'a>],
+}
+
 pub struct Processor;
 impl Processor {
   pub fn process(
@@ -33,7 +50,7 @@ impl Processor {
         Self::init_game(accounts,init, program_id)
       }
       GameInstruction::Play { mymove } => {
-        Self::play(program_id, accounts, mymove)
+        Self::play(program_id, accounts, mymove, WEAPON_SHOT)
       }
       GameInstruction::JoinGame { join } => {
         Self::join_the_game(accounts,program_id,join)
@@ -86,6 +103,87 @@ impl Processor {
       GameInstruction::Delete => {
         Self::delete_account(accounts)
       }
+      GameInstruction::PlaySalvo { salvo } => {
+        Self::play_salvo(program_id, accounts, salvo)
+      }
+      GameInstruction::AnnounceSunk { sunk } => {
+        Self::announce_sunk(program_id, accounts, sunk)
+      }
+      GameInstruction::UpdateConfig { config } => {
+        Self::update_config(accounts, program_id, config)
+      }
+      GameInstruction::ClaimvictoryFlagFall => {
+        Self::claim_victory_by_flag_fall(accounts,program_id)
+      }
+      GameInstruction::Resign => {
+        Self::resign(accounts,program_id)
+      }
+      GameInstruction::OfferDraw => {
+        Self::offer_draw(accounts,program_id)
+      }
+      GameInstruction::AcceptDraw => {
+        Self::accept_draw(accounts,program_id)
+      }
+      GameInstruction::CloseGame => {
+        Self::close_game(accounts,program_id)
+      }
+      GameInstruction::ProposeRematch { rematch } => {
+        Self::propose_rematch(accounts,program_id,rematch)
+      }
+      GameInstruction::AcceptRematch { rematch } => {
+        Self::accept_rematch(accounts,program_id,rematch)
+      }
+      GameInstruction::CancelRematch => {
+        Self::cancel_rematch(accounts,program_id)
+      }
+      GameInstruction::CommitSeriesGame { game } => {
+        Self::commit_series_game(accounts,program_id,game)
+      }
+      GameInstruction::InitFfa { init } => {
+        Self::init_ffa(accounts,program_id,init)
+      }
+      GameInstruction::JoinFfa { join } => {
+        Self::join_ffa(accounts,program_id,join)
+      }
+      GameInstruction::PlayFfa { shot } => {
+        Self::play_ffa(accounts,program_id,shot)
+      }
+      GameInstruction::ClaimFfaVictory => {
+        Self::claim_ffa_victory(accounts,program_id)
+      }
+      GameInstruction::ClaimFfaTimeout => {
+        Self::claim_ffa_timeout(accounts,program_id)
+      }
+      GameInstruction::AbortFfa => {
+        Self::abort_ffa(accounts,program_id)
+      }
+      GameInstruction::PlaySpecial { special } => {
+        Self::play(program_id, accounts, special.mymove, special.weapon)
+      }
+      GameInstruction::ClaimRevealTimeout => {
+        Self::claim_reveal_timeout(accounts,program_id)
+      }
+      GameInstruction::Finalize { report } => {
+        Self::finalize(program_id, accounts, report)
+      }
+      GameInstruction::ExpireGame => {
+        Self::expire_game(accounts,program_id)
+      }
+      GameInstruction::RegisterHost { host } => {
+        Self::register_host(accounts,program_id,host)
+      }
+      GameInstruction::RegisterReferrer { referrer } => {
+        Self::register_referrer(accounts,program_id,referrer)
+      }
+      GameInstruction::WithdrawReferralEarnings => {
+        Self::withdraw_referral_earnings(accounts,program_id)
+      }
+      GameInstruction::RevealFfa { claim } => {
+        Self::reveal_ffa(accounts,program_id,claim)
+      }
+      GameInstruction::TournamentFinalize { report } => {
+        Self::tournament_finalize(program_id, accounts, report)
+      }
 
     }
   }
@@ -99,35 +197,145 @@ impl Processor {
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
     let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let host_profile: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
     let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &init.gameseed, program_id).unwrap();
 
     if game_state.key != &game_state_check{panic!()}
     if game_state.owner != program_id{panic!()}
 
+    let config: ProgramConfig = Self::program_config(config_account, program_id)?;
+    let mut profile: HostProfile = Self::host_profile(host_profile, program_id)?;
+
+    if profile.enabled != 1 {panic!()}
+    if init.host_fee_bps != profile.fee_bps {panic!()}
+
     
     let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-    if state.initialized != 0 {panic!()}
+    if state.status != GameStatus::Uninitialized {panic!()}
     if init.gameseed.len() != 5 {panic!()}
+    if init.no_touching > 1 {panic!()}
+    if init.salvo > 1 {panic!()}
+    if init.time_unit > 1 {panic!()}
+    if init.time_unit == 0 && init.move_timeout < config.min_timeout_seconds {panic!()}
+    if init.time_unit == 0 && init.move_timeout > config.max_timeout_seconds {panic!()}
+    if init.time_unit == 1 && init.move_timeout < config.min_timeout_slots {panic!()}
+    if init.time_unit == 1 && init.move_timeout > config.max_timeout_slots {panic!()}
+    if init.time_unit == 0 && init.time_bank > config.max_time_bank_seconds {panic!()}
+    if init.time_unit == 0 && init.increment > config.max_increment_seconds {panic!()}
+    if init.time_unit == 1 && init.time_bank > config.max_time_bank_slots {panic!()}
+    if init.time_unit == 1 && init.increment > config.max_increment_slots {panic!()}
+    if init.time_bank == 0 && init.increment != 0 {panic!()}
+    if init.host_fee_bps < config.min_host_fee_bps || init.host_fee_bps > config.max_host_fee_bps {panic!()}
+    if init.log_moves > 1 {panic!()}
+    if init.best_of > 7 {panic!()}
+    if init.best_of > 1 && init.best_of & 1 == 0 {panic!()}
+    if init.salvo == 1 && init.special_weapons != 0 {panic!()}
+    if init.special_weapons > MAX_SPECIAL_WEAPONS {panic!()}
+    if init.close_on_settle > 1 {panic!()}
+
+    if init.log_moves == 1 {
+      let move_log: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      Self::create_move_log(initializer, game_state, move_log, program_id, init.log_bump)?;
+    }
+
+    //token wagers sit in a vault owned by the game, the game account itself only holds rent
+    if init.mint != [0;32] {
+      let mint: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let vault: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let initializer_token: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+      if mint.key.to_bytes() != init.mint {panic!()}
+      Self::open_vault(initializer, game_state, mint, vault, token_program, program_id, init.vault_bump)?;
+      Self::vault_deposit(initializer_token, initializer, vault, token_program, init.lamports)?;
+    }
+    //lamport wagers sit in an escrow owned by the program, never in the game account
+    else {
+      let escrow: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+      if !initializer.is_signer {panic!()}
+      state.escrowed = 0;
+      Self::open_escrow(initializer, game_state, escrow, program_id, init.escrow_bump)?;
+      Self::escrow_in(&mut state.escrowed, initializer, escrow, init.lamports)?;
+    }
+
+    //an optional referrer comes last, referral shares are only paid on lamport wagers
+    state.initializer_referrer = [0;32];
+    if let Ok(referrer) = next_account_info(accounts_iter) {
+      if init.mint != [0;32] {panic!()}
+      state.initializer_referrer = Self::refer(initializer, referrer, program_id)?;
+    }
 
-    state.host=host.key.to_bytes();
-    state.waiting=1;
+    state.host=profile.payout;
+    state.host_profile=host_profile.key.to_bytes();
     state.lamports=init.lamports;
+    state.host_fee_bps=init.host_fee_bps;
+    state.referral_share_bps=config.referral_share_bps;
+    state.guest_referrer=[0;32];
     state.initializer= initializer.key.to_bytes();
     state.initializergamehash= init.initializer_game_hash;
+    state.initializer_board_root= init.initializer_board_root;
     state.guest= [0;32];
     state.guestgamehash= [0;32];
-    state.initialized = 1;
+    state.guest_board_root= [0;32];
+    state.no_touching = init.no_touching;
+    state.board_width = Self::board_dimension(init.board_width);
+    state.board_height = Self::board_dimension(init.board_height);
+    state.fleet = Self::fleet_definition(init.fleet, state.board_width, state.board_height);
+    state.salvo = init.salvo;
+    state.draw_offer = 0;
+    state.result = GameResult{winner: 0, reason: 0, initializer_score: 0, guest_score: 0, settled_at: 0};
+    state.close_on_settle = init.close_on_settle;
+    state.expires_at = init.expires_at;
+    state.mint = init.mint;
+    state.vault_bump = init.vault_bump;
+    state.escrow_bump = init.escrow_bump;
+    state.rematch_offer = 0;
+    state.invited_guest = init.invited_guest;
+    state.join_key = init.join_key;
+    state.log_moves = init.log_moves;
+    state.best_of = init.best_of;
+    state.initializer_series_wins = 0;
+    state.guest_series_wins = 0;
+    state.series_commits = 0;
+    state.series_games = 0;
+    state.special_weapons = init.special_weapons;
+    state.initializer_specials_used = 0;
+    state.guest_specials_used = 0;
+    state.last_weapon = WEAPON_SHOT;
+    state.special_log = [SpecialRecord::default();2*MAX_SPECIAL_WEAPONS as usize];
+    state.special_count = 0;
+    state.reveal_deadline = 0;
+    state.initializer_reveal = 0;
+    state.guest_reveal = 0;
+    state.move_timeout = init.move_timeout;
+    state.time_unit = init.time_unit;
+    state.time_bank = init.time_bank;
+    state.increment = init.increment;
+    state.initializer_time_bank = init.time_bank;
+    state.guest_time_bank = init.time_bank;
+    if !init.initializer_ship_hashes.is_empty() && init.initializer_ship_hashes.len() != state.fleet.len() {panic!()}
+    state.initializer_ship_hash_count = init.initializer_ship_hashes.len() as u8;
+    state.initializer_ship_hashes = Self::ship_hash_buffer(&init.initializer_ship_hashes);
+    state.guest_ship_hashes = [[0;32];MAX_FLEET_SHIPS];
+    state.guest_ship_hash_count = 0;
+    state.initializer_sunk_mask = 0;
+    state.guest_sunk_mask = 0;
+    Self::transition(&mut state.status, GameStatus::Open)?;
     state.gameseed = init.gameseed;
 
 
-    let r: u64 = Rent::default().minimum_balance(322);
-    if **game_state.lamports.borrow() < init.lamports + r {panic!()}
+    let r: u64 = Rent::get()?.minimum_balance(game_state.data_len());
+    if **game_state.lamports.borrow() < r {panic!()}
+
+    profile.games_hosted += 1;
 
     state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+    profile.serialize(&mut &mut host_profile.data.borrow_mut()[..])?;
    
     Ok(())
   }
@@ -151,22 +359,55 @@ impl Processor {
 
     if &host_address != host.key {panic!()}
 
-    if state.initialized != 1 {panic!()}
+    if state.status != GameStatus::Open {panic!()}
 
-    let clock: Clock= Clock::get()?;
-    let current_time: u64 = clock.unix_timestamp as u64;
+    let current_time: u64 = Self::current_time(state.time_unit)?;
 
-    if join.guest_game_hash.len() != 44 {panic!()}//////////////////////////////////////////////////////
+    if state.expires_at != 0 && current_time >= state.expires_at {panic!()}
+    if state.initializer_board_root == [0;32] && join.guest_board_root != [0;32] {panic!()}
+    if state.initializer_board_root != [0;32] && join.guest_board_root == [0;32] {panic!()}
+    if join.guest_ship_hashes.len() != state.initializer_ship_hash_count as usize {panic!()}
+
+    if state.invited_guest != [0;32]{
+      if guest.key.to_bytes() != state.invited_guest {panic!()}
+      if !guest.is_signer {panic!()}
+    }
+    //the passphrase itself never goes on chain, it seeds a keypair (with the game address
+    //as salt) that has to co-sign the join, so the join can't be replayed for another guest
+    if state.join_key != [0;32]{
+      let passphrase_key: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      if passphrase_key.key.to_bytes() != state.join_key {panic!()}
+      if !passphrase_key.is_signer {panic!()}
+      if !guest.is_signer {panic!()}
+    }
 
     state.guest = guest.key.to_bytes();
     state.guestgamehash = join.guest_game_hash;
-    state.waiting = 2;
+    state.guest_board_root = join.guest_board_root;
+    state.guest_ship_hash_count = join.guest_ship_hashes.len() as u8;
+    state.guest_ship_hashes = Self::ship_hash_buffer(&join.guest_ship_hashes);
     state.lastplaytime = current_time;
+    state.first_turn = 1;
     state.whoseturn = 1;
-    state.initialized = 2;
+    Self::transition(&mut state.status, GameStatus::Active)?;
+
+    if state.mint != [0;32] { // temp_account is the guest's token account
+      let vault: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let token_program: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      if !guest.is_signer {panic!()}
+      Self::check_vault(&state, game_state, vault, token_program, program_id)?;
+      Self::vault_deposit(temp_account, guest, vault, token_program, state.lamports)?;
+    }else{ // temp_account is the game's escrow
+      if !guest.is_signer {panic!()}
+      let stake: u64 = state.lamports;
+      Self::check_escrow(state.escrow_bump, state.escrowed, state.lamports, game_state, temp_account, program_id)?;
+      Self::escrow_in(&mut state.escrowed, guest, temp_account, stake)?;
+    }
 
-    **temp_account.lamports.borrow_mut()-= state.lamports;
-    **game_state.lamports.borrow_mut()+= state.lamports;
+    if let Ok(referrer) = next_account_info(accounts_iter) {
+      if state.mint != [0;32] {panic!()}
+      state.guest_referrer = Self::refer(guest, referrer, program_id)?;
+    }
 
     state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
 
@@ -175,7 +416,8 @@ impl Processor {
   fn play(        
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    m: Move,) -> ProgramResult {
+    m: Move,
+    weapon: u8) -> ProgramResult {
 
 
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
@@ -197,12 +439,15 @@ impl Processor {
     if guest.key != &guest_check{panic!()}
     if host.key != &host_check{panic!()}
     if game_state.key != &game_state_check{panic!()}
-    if state.initialized != 2 {panic!()}
-    if m.mymove > 110 {panic!()}
-    if m.mymove < 11 {panic!()}
+    if state.status != GameStatus::Active {panic!()}
+    if state.salvo != 0 {panic!()}
+    if m.mymove > state.board_width*(state.board_height + 1) {panic!()}
+    if m.mymove < state.board_width + 1 {panic!()}
+    if weapon > WEAPON_AIRSTRIKE {panic!()}
+    if weapon != WEAPON_SHOT && state.special_weapons == 0 {panic!()}
+    if state.lastmove == 0 && m.opponents_last_move_is_hit != 0 {panic!()}
 
-    let clock: Clock= Clock::get()?;
-    let current_time: u64 = clock.unix_timestamp as u64;
+    let current_time: u64 = Self::current_time(state.time_unit)?;
 
     let mut turn:u8 = 0;
     let hit:u8 = m.opponents_last_move_is_hit;
@@ -212,243 +457,86 @@ impl Processor {
 
     if state.whoseturn == 1{ // 1 initializer
       if !initializer.is_signer{panic!()};
+      if state.last_weapon == WEAPON_SHOT {
+        if hit > 1 {panic!()}
+        if state.initializer_board_root != [0;32] && state.lastmove != 0
+          && !Self::verify_cell_report(&state.initializer_board_root, state.lastmove, hit, &m.salt, &m.proof) {
+          return Err(FalseHitReport.into());
+        }
+        state.ghits += hit;
+        state.initializer_board_state[index2] += hit;
+      }else{
+        state.ghits += Self::resolve_special(&mut state, 1, hit)?;
+      }
       turn = 2;
       state.ishots += 1;
-      state.ghits += hit;
-      if state.guest_board_state[index] != 0{panic!()};
-      state.guest_board_state[index] += 1;
-      state.initializer_board_state[index2] += hit;
+      if weapon == WEAPON_SHOT {
+        if state.guest_board_state[index] != 0{panic!()};
+        state.guest_board_state[index] += 1;
+      }else{
+        if state.initializer_specials_used >= state.special_weapons {panic!()}
+        state.initializer_specials_used += 1;
+      }
     }//hit 2
      //miss 1
      //unchecked 0
 
     if state.whoseturn == 2{ // 2 guest
       if !guest.is_signer{panic!()};
+      if state.last_weapon == WEAPON_SHOT {
+        if hit > 1 {panic!()}
+        if state.guest_board_root != [0;32] && state.lastmove != 0
+          && !Self::verify_cell_report(&state.guest_board_root, state.lastmove, hit, &m.salt, &m.proof) {
+          return Err(FalseHitReport.into());
+        }
+        state.ihits += hit;
+        state.guest_board_state[index2] += hit;
+      }else{
+        state.ihits += Self::resolve_special(&mut state, 2, hit)?;
+      }
       turn = 1;
       state.gshots += 1;
-      state.ihits += hit;
-      if state.initializer_board_state[index] != 0{panic!()};
-      state.initializer_board_state[index] += 1;
-      state.guest_board_state[index2] += hit;
+      if weapon == WEAPON_SHOT {
+        if state.initializer_board_state[index] != 0{panic!()};
+        state.initializer_board_state[index] += 1;
+      }else{
+        if state.guest_specials_used >= state.special_weapons {panic!()}
+        state.guest_specials_used += 1;
+      }
+    }
+
+    if state.log_moves == 1 {
+      let move_log: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let mover: &AccountInfo<'_> = if state.whoseturn == 1 {initializer} else {guest};
+      let mut records: Vec<MoveRecord> = Vec::new();
+      if state.lastmove != 0 {
+        let result: u8 = if state.last_weapon == WEAPON_SHOT {1 + hit} else {hit};
+        records.push(MoveRecord{shooter: turn, weapon: state.last_weapon, cell: state.lastmove, result, timestamp: current_time});
+      }
+      records.push(MoveRecord{shooter: state.whoseturn, weapon, cell: m.mymove, result: 0, timestamp: current_time});
+      Self::append_move_log(mover, game_state, move_log, program_id, records)?;
     }
 
+    Self::charge_clock(&mut state, current_time);
+
+    if state.draw_offer != 0 && state.draw_offer != state.whoseturn {state.draw_offer = 0;}
+
     state.lastplaytime = current_time;
     state.whoseturn  = turn;
     state.lastmove = m.mymove;
+    state.last_weapon = weapon;
+
+    Self::check_fleet_down(&mut state, current_time)?;
 
     state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
 
 
     Ok(())
   }
-  fn claim_victory_by_score(        
-    accounts: &[AccountInfo],
+  fn play_salvo(
     program_id: &Pubkey,
-    claim: ClaimVictory,) -> ProgramResult {
-
-      let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
-
-      let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-      let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-      let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-      let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-  
-      let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
-  
-
-  
-      let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
-      let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
-      let host_check: Pubkey = Pubkey::new_from_array(state.host);
-      let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
-  
-      if initializer.key != &initializer_check{panic!()}
-      if guest.key != &guest_check{panic!()}
-      if host.key != &host_check{panic!()}
-      if game_state.key != &game_state_check{panic!()}
-      if state.initialized != 2 {panic!()}
-
-      let mut game_arr:[u8;17]=[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0];
-
-    if claim.s1ax == 1{
-      if claim.s1cox > 6 {panic!()}
-      if claim.s1cox < 1 {panic!()}
-      if claim.s1coy > 10 {panic!()}
-      if claim.s1coy < 1 {panic!()}
-      game_arr[0] = claim.s1cox+(claim.s1coy*10);
-      game_arr[1] = (claim.s1cox + 1)+(claim.s1coy*10);
-      game_arr[2] = (claim.s1cox + 2)+(claim.s1coy*10);
-      game_arr[3] = (claim.s1cox + 3)+(claim.s1coy*10);
-      game_arr[4] = (claim.s1cox + 4)+(claim.s1coy*10);
-    }
-    if claim.s1ax == 2{
-      if claim.s1coy > 6 {panic!()}
-      if claim.s1coy < 1 {panic!()}
-      if claim.s1cox > 10 {panic!()}
-      if claim.s1cox < 1 {panic!()}
-      game_arr[0] = (claim.s1coy*10)+(claim.s1cox);
-      game_arr[1] = ((claim.s1coy + 1)*10)+(claim.s1cox);
-      game_arr[2] = ((claim.s1coy + 2)*10)+(claim.s1cox);
-      game_arr[3] = ((claim.s1coy + 3)*10)+(claim.s1cox);
-      game_arr[4] = ((claim.s1coy + 4)*10)+(claim.s1cox);
-    }
-    if claim.s2ax == 1{
-      if claim.s2cox > 6 {panic!()}
-      if claim.s2cox < 1 {panic!()}
-      if claim.s2coy > 10 {panic!()}
-      if claim.s2coy < 1 {panic!()}
-      game_arr[5] = claim.s2cox+(claim.s2coy*10);
-      game_arr[6] = (claim.s2cox + 1)+(claim.s2coy*10);
-      game_arr[7] = (claim.s2cox + 2)+(claim.s2coy*10);
-      game_arr[8] = (claim.s2cox + 3)+(claim.s2coy*10);
-    }
-    if claim.s2ax == 2{
-      if claim.s2coy > 6 {panic!()}
-      if claim.s2coy < 1 {panic!()}
-      if claim.s2cox > 10 {panic!()}
-      if claim.s2cox < 1 {panic!()}
-      game_arr[5] = (claim.s2coy*10)+(claim.s2cox);
-      game_arr[6] = ((claim.s2coy + 1)*10)+(claim.s2cox);
-      game_arr[7] = ((claim.s2coy + 2)*10)+(claim.s2cox);
-      game_arr[8] = ((claim.s2coy + 3)*10)+(claim.s2cox);
-    }
-    if claim.s3ax == 1{
-      if claim.s3cox > 6 {panic!()}
-      if claim.s3cox < 1 {panic!()}
-      if claim.s3coy > 10 {panic!()}
-      if claim.s3coy < 1 {panic!()}
-      game_arr[9] = claim.s3cox+(claim.s3coy*10);
-      game_arr[10] = (claim.s3cox + 1)+(claim.s3coy*10);
-      game_arr[11] = (claim.s3cox + 2)+(claim.s3coy*10);
-    }
-    if claim.s3ax == 2{
-      if claim.s3coy > 6 {panic!()}
-      if claim.s3coy < 1 {panic!()}
-      if claim.s3cox > 10 {panic!()}
-      if claim.s3cox < 1 {panic!()}
-      game_arr[9] = (claim.s3coy*10)+(claim.s3cox);
-      game_arr[10] = ((claim.s3coy + 1)*10)+(claim.s3cox);
-      game_arr[11] = ((claim.s3coy + 2)*10)+(claim.s3cox);
-    }
-    if claim.s4ax == 1{
-      if claim.s4cox > 6 {panic!()}
-      if claim.s4cox < 1 {panic!()}
-      if claim.s4coy > 10 {panic!()}
-      if claim.s4coy < 1 {panic!()}
-      game_arr[12] = claim.s4cox+(claim.s4coy*10);
-      game_arr[13] = (claim.s4cox + 1)+(claim.s4coy*10);
-      game_arr[14] = (claim.s4cox + 2)+(claim.s4coy*10);
-    }
-    if claim.s4ax == 2{
-      if claim.s4coy > 6 {panic!()}
-      if claim.s4coy < 1 {panic!()}
-      if claim.s4cox > 10 {panic!()}
-      if claim.s4cox < 1 {panic!()}
-      game_arr[12] = (claim.s4coy*10)+(claim.s4cox);
-      game_arr[13] = ((claim.s4coy + 1)*10)+(claim.s4cox);
-      game_arr[14] = ((claim.s4coy + 2)*10)+(claim.s4cox);
-    }
-    if claim.s5ax == 1{
-      if claim.s5cox > 6 {panic!()}
-      if claim.s5cox < 1 {panic!()}
-      if claim.s5coy > 10 {panic!()}
-      if claim.s5coy < 1 {panic!()}
-      game_arr[15] = claim.s5cox+(claim.s5coy*10);
-      game_arr[16] = (claim.s5cox + 1)+(claim.s5coy*10);
-    }
-    if claim.s5ax == 2{
-      if claim.s5coy > 6 {panic!()}
-      if claim.s5coy < 1 {panic!()}
-      if claim.s5cox > 10 {panic!()}
-      if claim.s5cox < 1 {panic!()}
-      game_arr[15] = (claim.s5coy*10)+(claim.s5cox);
-      game_arr[16] = ((claim.s5coy + 1)*10)+(claim.s5cox);
-    }
-
-    let game_hash: keccak::Hash = keccak::hashv(&[&claim.seed.to_string().as_bytes(),&game_arr,&claim.seed.to_string().as_bytes()]);
-
-
-    if claim.claims == 1 {
-      if state.initializergamehash != game_hash.0{panic!()}
-    }
-    else {
-      if state.guestgamehash != game_hash.0{panic!()}
-    }
-
-    if claim.claims == 1 {
-      for x in 1..18 {
-        let u = x as usize;
-        let s = game_arr[u] as usize;
-        if state.initializer_board_state[s] > 0 && state.initializer_board_state[s] != 2 {
-          state.ihits = 0;
-          state.ghits = 17;
-        }
-      }
-    }else{
-      for x in 1..18 {
-        let u = x as usize;
-        let s = game_arr[u] as usize;
-        if state.guest_board_state[s] > 0 && state.guest_board_state[s] != 2 {
-          state.ihits = 17;
-          state.ghits = 0;
-        }
-      }
-    }
-
-      let mut draw:bool=false;
-      let mut iwins:bool=false;
-      let mut gwins:bool=false;
-  
-      if state.ishots != state.gshots{panic!()}
-      if state.ihits == 17{iwins = true;}
-      if state.ghits == 17{gwins = true;}
-      if gwins && iwins{draw = true;iwins = false;gwins = false;}
-      
-      if iwins == true{
-  
-        let host_fee = (state.lamports/100)*10;
-  
-        **game_state.lamports.borrow_mut()-= host_fee;
-        **host.lamports.borrow_mut()+= host_fee;
-  
-        let value = **game_state.lamports.borrow();
-  
-        **game_state.lamports.borrow_mut()-= value;
-        **initializer.lamports.borrow_mut()+= value;
-  
-      }
-      if gwins == true{
-        let host_fee = (state.lamports/100)*10;
-  
-        **game_state.lamports.borrow_mut()-= host_fee;
-        **host.lamports.borrow_mut()+= host_fee;
-  
-        let rew = (&state.lamports*2)-&host_fee;
-  
-        **game_state.lamports.borrow_mut()-= rew;
-        **guest.lamports.borrow_mut()+= rew;
-  
-        let value = **game_state.lamports.borrow();
-  
-        **game_state.lamports.borrow_mut()-= value;
-        **initializer.lamports.borrow_mut()+= value;
-      }
-      if draw == true{
-        **game_state.lamports.borrow_mut()-= state.lamports;
-        **guest.lamports.borrow_mut()+= state.lamports;
-  
-        let value = **game_state.lamports.borrow();
-  
-        **game_state.lamports.borrow_mut()-= value;
-        **initializer.lamports.borrow_mut()+= value;
-      }
-  
-      Ok(())
-
-  }
-  fn claim_victory_by_time(        
     accounts: &[AccountInfo],
-    program_id: &Pubkey,) -> ProgramResult {
+    salvo: Salvo,) -> ProgramResult {
 
 
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
@@ -458,7 +546,7 @@ impl Processor {
     let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    let state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
 
     let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
@@ -470,1165 +558,4300 @@ impl Processor {
     if guest.key != &guest_check{panic!()}
     if host.key != &host_check{panic!()}
     if game_state.key != &game_state_check{panic!()}
-    if state.initialized != 2 {panic!()}
+    if state.status != GameStatus::Active {panic!()}
+    if state.salvo != 1 {panic!()}
+    if salvo.opponents_last_salvo_hits.len() != state.last_salvo_len as usize {panic!()}
 
-    let clock: Clock= Clock::get()?;
-    let current_time: u64 = clock.unix_timestamp as u64;
+    let max_cell: u8 = state.board_width*(state.board_height + 1);
+    let min_cell: u8 = state.board_width + 1;
+    let ships: u8 = state.fleet.len() as u8;
+    let pending_len: usize = state.last_salvo_len as usize;
 
-    let time_passed: u64 = &current_time - &state.lastplaytime;
+    let current_time: u64 = Self::current_time(state.time_unit)?;
 
-    if time_passed<120{panic!()}
+    let mut turn:u8 = 0;
 
-    let mut gwins:bool=false;
-    let mut iwins:bool=false;
 
-    if state.whoseturn == 1{
-      gwins = true;
-    }
-    if state.whoseturn == 2{
-      iwins = true;
+    if state.whoseturn == 1{ // 1 initializer
+      if !initializer.is_signer{panic!()};
+      turn = 2;
+      if state.initializer_board_root != [0;32] && pending_len > MAX_PROVEN_REPORTS {panic!()}
+
+      for (i, cell) in state.last_salvo[..pending_len].iter().enumerate() {
+        let hit: u8 = salvo.opponents_last_salvo_hits[i];
+        if hit > 1 {panic!()}
+        if state.initializer_board_root != [0;32] {
+          if salvo.salts.len() != pending_len {panic!()}
+          if salvo.proofs.len() != pending_len {panic!()}
+          if !Self::verify_cell_report(&state.initializer_board_root, *cell, hit, &salvo.salts[i], &salvo.proofs[i]) {
+            return Err(FalseHitReport.into());
+          }
+        }
+        state.ghits += hit;
+        state.initializer_board_state[*cell as usize] += hit;
+      }
+
+      if state.initializer_ship_hash_count != 0 && salvo.ships_sunk != 0 {panic!()}
+      state.gsunk += salvo.ships_sunk;
+      if state.gsunk > ships {panic!()}
+
+      let allowed: u8 = ships - state.gsunk;
+      if salvo.targets.is_empty() {panic!()}
+      if salvo.targets.len() > allowed as usize {panic!()}
+
+      for target in salvo.targets.iter() {
+        if *target > max_cell {panic!()}
+        if *target < min_cell {panic!()}
+        let index: usize = *target as usize;
+        if state.guest_board_state[index] != 0{panic!()};
+        state.guest_board_state[index] += 1;
+      }
+      state.ishots += 1;
     }
-    if iwins == true{
 
-      let host_fee: u64 = (state.lamports/100)*10;
+    if state.whoseturn == 2{ // 2 guest
+      if !guest.is_signer{panic!()};
+      turn = 1;
+      if state.guest_board_root != [0;32] && pending_len > MAX_PROVEN_REPORTS {panic!()}
+
+      for (i, cell) in state.last_salvo[..pending_len].iter().enumerate() {
+        let hit: u8 = salvo.opponents_last_salvo_hits[i];
+        if hit > 1 {panic!()}
+        if state.guest_board_root != [0;32] {
+          if salvo.salts.len() != pending_len {panic!()}
+          if salvo.proofs.len() != pending_len {panic!()}
+          if !Self::verify_cell_report(&state.guest_board_root, *cell, hit, &salvo.salts[i], &salvo.proofs[i]) {
+            return Err(FalseHitReport.into());
+          }
+        }
+        state.ihits += hit;
+        state.guest_board_state[*cell as usize] += hit;
+      }
 
-      **game_state.lamports.borrow_mut()-= host_fee;
-      **host.lamports.borrow_mut()+= host_fee;
+      if state.guest_ship_hash_count != 0 && salvo.ships_sunk != 0 {panic!()}
+      state.isunk += salvo.ships_sunk;
+      if state.isunk > ships {panic!()}
 
-      let value: u64 = **game_state.lamports.borrow();
+      let allowed: u8 = ships - state.isunk;
+      if salvo.targets.is_empty() {panic!()}
+      if salvo.targets.len() > allowed as usize {panic!()}
 
-      **game_state.lamports.borrow_mut()-= value;
-      **initializer.lamports.borrow_mut()+= value;
+      for target in salvo.targets.iter() {
+        if *target > max_cell {panic!()}
+        if *target < min_cell {panic!()}
+        let index: usize = *target as usize;
+        if state.initializer_board_state[index] != 0{panic!()};
+        state.initializer_board_state[index] += 1;
+      }
+      state.gshots += 1;
+    }
 
+    if state.log_moves == 1 {
+      let move_log: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let mover: &AccountInfo<'_> = if state.whoseturn == 1 {initializer} else {guest};
+      let mut records: Vec<MoveRecord> = Vec::new();
+      for (i, cell) in state.last_salvo[..pending_len].iter().enumerate() {
+        records.push(MoveRecord{shooter: turn, weapon: WEAPON_SHOT, cell: *cell, result: 1 + salvo.opponents_last_salvo_hits[i], timestamp: current_time});
+      }
+      for target in salvo.targets.iter() {
+        records.push(MoveRecord{shooter: state.whoseturn, weapon: WEAPON_SHOT, cell: *target, result: 0, timestamp: current_time});
+      }
+      Self::append_move_log(mover, game_state, move_log, program_id, records)?;
     }
-    if gwins == true{
-      let host_fee: u64 = (state.lamports/100)*10;
 
-      **game_state.lamports.borrow_mut()-= host_fee;
-      **host.lamports.borrow_mut()+= host_fee;
+    Self::charge_clock(&mut state, current_time);
 
-      let rew: u64 = (&state.lamports*2)-&host_fee;
+    if state.draw_offer != 0 && state.draw_offer != state.whoseturn {state.draw_offer = 0;}
 
-      **game_state.lamports.borrow_mut()-= rew;
-      **guest.lamports.borrow_mut()+= rew;
+    state.lastplaytime = current_time;
+    state.whoseturn  = turn;
+    //targets never outnumber the fleet, so they always fit the fixed buffer
+    state.last_salvo = [0;MAX_FLEET_SHIPS];
+    state.last_salvo[..salvo.targets.len()].copy_from_slice(&salvo.targets);
+    state.last_salvo_len = salvo.targets.len() as u8;
 
-      let value: u64 = **game_state.lamports.borrow();
+    Self::check_fleet_down(&mut state, current_time)?;
+
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
 
-      **game_state.lamports.borrow_mut()-= value;
-      **initializer.lamports.borrow_mut()+= value;
-    }
 
     Ok(())
   }
-  fn abort_game(        
+  fn finalize(
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
-    program_id: &Pubkey,) -> ProgramResult {
+    report: FinalReport,) -> ProgramResult {
+
 
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
     let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    let state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
 
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
     let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
     let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
     if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
     if game_state.key != &game_state_check{panic!()}
-    if state.initialized != 1 {panic!()}
+    if state.status != GameStatus::Active && state.status != GameStatus::AwaitingReveal {panic!()}
+
+    //the defender is whoever the pending shots were fired at
+    let defender: u8 = state.whoseturn;
+    if defender == 1 && !initializer.is_signer {panic!()}
+    if defender == 2 && !guest.is_signer {panic!()}
+    if defender != 1 && defender != 2 {panic!()}
+    if defender == 1 && state.initializer_reveal != 0 {panic!()}
+    if defender == 2 && state.guest_reveal != 0 {panic!()}
+
+    let mut pending: Vec<u8> = state.last_salvo[..state.last_salvo_len as usize].to_vec();
+    if state.salvo == 0 && state.lastmove != 0 {pending = vec![state.lastmove];}
+    if pending.is_empty() {panic!()}
+    if report.hits.is_empty() || report.hits.len() > pending.len() {panic!()}
+
+    let root: [u8;32] = if defender == 1 {state.initializer_board_root} else {state.guest_board_root};
+    //proofs are too big to report a whole salvo at once, the first cells are reported and the rest stay pending
+    if root != [0;32] && report.hits.len() > MAX_PROVEN_REPORTS {panic!()}
+    let reported: usize = report.hits.len();
+    let current_time: u64 = Self::current_time(state.time_unit)?;
+    let mut records: Vec<MoveRecord> = Vec::new();
+
+    for (i, cell) in pending[..reported].iter().enumerate() {
+      let hit: u8 = report.hits[i];
+      let mut scored: u8 = hit;
+
+      if state.salvo == 0 && state.last_weapon != WEAPON_SHOT {
+        scored = Self::resolve_special(&mut state, defender, hit)?;
+        records.push(MoveRecord{shooter: 3 - defender, weapon: state.last_weapon, cell: *cell, result: hit, timestamp: current_time});
+      }else{
+        if hit > 1 {panic!()}
+        if root != [0;32] {
+          if report.salts.len() != reported {panic!()}
+          if report.proofs.len() != reported {panic!()}
+          if !Self::verify_cell_report(&root, *cell, hit, &report.salts[i], &report.proofs[i]) {
+            return Err(FalseHitReport.into());
+          }
+        }
+        if defender == 1 {state.initializer_board_state[*cell as usize] += hit;}
+        else {state.guest_board_state[*cell as usize] += hit;}
+        records.push(MoveRecord{shooter: 3 - defender, weapon: WEAPON_SHOT, cell: *cell, result: 1 + hit, timestamp: current_time});
+      }
 
-    let value: u64 = **game_state.lamports.borrow();
+      if defender == 1 {state.ghits += scored;}
+      else {state.ihits += scored;}
+    }
 
-    **game_state.lamports.borrow_mut()-= value;
-    **initializer.lamports.borrow_mut()+= value;
+    if state.log_moves == 1 {
+      let move_log: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let payer: &AccountInfo<'_> = if defender == 1 {initializer} else {guest};
+      Self::append_move_log(payer, game_state, move_log, program_id, records)?;
+    }
+
+    if reported < pending.len() {
+      //the unreported cells move to the front and stay pending for the next batch or move
+      state.last_salvo.copy_within(reported..pending.len(), 0);
+      state.last_salvo_len -= reported as u8;
+      state.last_salvo[state.last_salvo_len as usize..].fill(0);
+    }
+    else {
+      //nothing is left pending, the next move reports nothing and the reveal counts nothing extra
+      state.lastmove = 0;
+      state.last_salvo = [0;MAX_FLEET_SHIPS];
+      state.last_salvo_len = 0;
+      state.last_weapon = WEAPON_SHOT;
+    }
+
+    if state.status == GameStatus::Active {
+      Self::check_fleet_down(&mut state, current_time)?;
+    }
+
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
 
     Ok(())
   }
-  fn init_tournament(        
+  fn announce_sunk(
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
-    t: Tournament) -> ProgramResult {
+    sunk: SunkShip,) -> ProgramResult {
 
 
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
     let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    let authority: Pubkey = Pubkey::from_str("4YbLBRXwseG1NuyJbteSD5u81Q2QjFqJBp6JmxwYBKYm").unwrap();
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-    if initializer.key != &authority {panic!()}
-    if !initializer.is_signer{panic!()}
 
-    t.serialize(&mut &mut tournament.data.borrow_mut()[..])?;
-    
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
-    Ok(())
-  }
-  fn join_tournament(        
-    accounts: &[AccountInfo],
-    program_id: &Pubkey,) -> ProgramResult {
+    if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::Active {panic!()}
+
+    let ship: usize = sunk.ship as usize;
+    if ship >= state.fleet.len() {panic!()}
+    if sunk.placement.length != state.fleet[ship] {return Err(FleetMismatch.into());}
+
+    let cells: Vec<u8> = Self::ship_cells(&sunk.placement, state.board_width, state.board_height)?;
+    let commitment: [u8;32] = Self::ship_commitment(&sunk.placement, &sunk.salt);
+    let bit: u16 = 1 << ship;
+
+    if sunk.owner == 1 { // initializer's ship, sunk by guest
+      if !initializer.is_signer{panic!()}
+      if state.initializer_ship_hash_count == 0 {panic!()}
+      if state.initializer_sunk_mask & bit != 0 {panic!()}
+      if state.initializer_ship_hashes[ship] != commitment {return Err(FalseShipReveal.into());}
+      for cell in cells.iter() {
+        if state.initializer_board_state[*cell as usize] != 2 {return Err(ShipNotSunk.into());}
+      }
+      state.initializer_sunk_mask |= bit;
+      state.gsunk += 1;
+    }
+    else if sunk.owner == 2 { // guest's ship, sunk by initializer
+      if !guest.is_signer{panic!()}
+      if state.guest_ship_hash_count == 0 {panic!()}
+      if state.guest_sunk_mask & bit != 0 {panic!()}
+      if state.guest_ship_hashes[ship] != commitment {return Err(FalseShipReveal.into());}
+      for cell in cells.iter() {
+        if state.guest_board_state[*cell as usize] != 2 {return Err(ShipNotSunk.into());}
+      }
+      state.guest_sunk_mask |= bit;
+      state.isunk += 1;
+    }
+    else {panic!()}
 
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
 
-    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-    let participant: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let tournament_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let counter: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    Ok(())
+  }
+  fn claim_victory_by_score(        
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    claim: ClaimVictory,) -> ProgramResult {
 
-    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
-    let mut t_counter: TournamentCounter = TournamentCounter::try_from_slice(&counter.data.borrow())?;
+      let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-    let value: u64 = **tournament_account.lamports.borrow();
+      let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+  
+      let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
+  
 
-    if value < t.entrance_fee{panic!()}
-    if tournament.is_writable{panic!()}
-    if tournament.owner != program_id{panic!()}
-    if t.is_init != 1 {panic!()}
-    if tournament_account.owner != program_id{panic!()}
-    if t_counter.player_participating >= t_counter.capacity{panic!()}
+  
+      let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+      let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+      let host_check: Pubkey = Pubkey::new_from_array(state.host);
+      let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
+  
+      if initializer.key != &initializer_check{panic!()}
+      if guest.key != &guest_check{panic!()}
+      if host.key != &host_check{panic!()}
+      if game_state.key != &game_state_check{panic!()}
+      if state.status != GameStatus::AwaitingReveal {panic!()}
 
+    let game_arr: Vec<u8> = Self::expand_fleet(&claim.fleet, &state.fleet, state.board_width, state.board_height, state.no_touching)?;
 
-    t_counter.player_participating += 1;
+    let game_hash: keccak::Hash = keccak::hashv(&[claim.seed.to_string().as_bytes(),&game_arr[..],claim.seed.to_string().as_bytes()]);
 
-    let mut str_no: String = String::new();
-    let str_tournament_id: String = t.tournament_id;
-    let str_counter_no: String = t_counter.counter_no.to_string();
-    let str_player_no: String = t_counter.player_participating.to_string();
-    let mut somestr: String = String::from("pppppp");
-    let mut somestr2: String = String::from("cccc");
-    let revstr2: String = str_counter_no.chars().rev().collect::<String>();
-    let revstr: String = str_player_no.chars().rev().collect::<String>();
-    let offset: &usize = &revstr.len();
-    let offset2: &usize = &revstr2.len();
-    somestr.replace_range(..offset, &revstr);
-    somestr2.replace_range(..offset2, &revstr2);
-    str_no += &somestr.chars().rev().collect::<String>();
-    str_no += &somestr2.chars().rev().collect::<String>();
-    str_no += &str_tournament_id;    
+    //the shots fired in the move that ended the game were never reported, the reveal answers them
+    let mut pending: Vec<u8> = state.last_salvo[..state.last_salvo_len as usize].to_vec();
+    if state.salvo == 0 && state.last_weapon == WEAPON_SHOT {pending = vec![state.lastmove];}
 
+    if claim.claims == 1 {
+      if state.initializer_reveal != 0 {panic!()}
+      if state.initializergamehash != game_hash.0{panic!()}
+      if state.whoseturn != 1 {pending = Vec::new();}
 
-    let mut opp: u32 = t_counter.player_participating + 1;
+      let (consistent, pending_hits): (bool, u8) = Self::board_matches(&game_arr, &state.initializer_board_state, &pending);
+      let mut honest: bool = consistent;
+      if state.initializer_ship_hash_count != 0 && !Self::ships_match_commitments(&claim.fleet, &claim.ship_salts, &state.initializer_ship_hashes[..state.initializer_ship_hash_count as usize]) {
+        honest = false;
+      }
+      if (state.salvo == 1 || state.initializer_ship_hash_count != 0) && Self::ships_sunk(&game_arr, &state.fleet, &state.initializer_board_state) != state.gsunk {
+        honest = false;
+      }
+      if !Self::specials_match_board(&state, 1, &game_arr) {honest = false;}
 
-    if t_counter.player_participating%2 == 0{
-      opp = t_counter.player_participating - 1;
+      state.ghits += pending_hits;
+      if honest {state.ghits += Self::answer_pending_special(&mut state, 1, &game_arr)?;}
+      state.initializer_reveal = if honest {1} else {2};
     }
+    else if claim.claims == 2 {
+      if state.guest_reveal != 0 {panic!()}
+      if state.guestgamehash != game_hash.0{panic!()}
+      if state.whoseturn != 2 {pending = Vec::new();}
 
-    let tournament_account_check: Pubkey = Pubkey::create_with_seed(participant.key, &str_no, program_id).unwrap();
+      let (consistent, pending_hits): (bool, u8) = Self::board_matches(&game_arr, &state.guest_board_state, &pending);
+      let mut honest: bool = consistent;
+      if state.guest_ship_hash_count != 0 && !Self::ships_match_commitments(&claim.fleet, &claim.ship_salts, &state.guest_ship_hashes[..state.guest_ship_hash_count as usize]) {
+        honest = false;
+      }
+      if (state.salvo == 1 || state.guest_ship_hash_count != 0) && Self::ships_sunk(&game_arr, &state.fleet, &state.guest_board_state) != state.isunk {
+        honest = false;
+      }
+      if !Self::specials_match_board(&state, 2, &game_arr) {honest = false;}
 
-    if tournament_account.key != &tournament_account_check{panic!()}
+      state.ihits += pending_hits;
+      if honest {state.ihits += Self::answer_pending_special(&mut state, 2, &game_arr)?;}
+      state.guest_reveal = if honest {1} else {2};
+    }
+    else {panic!()}
 
-    let t_account: TournamentAccount = TournamentAccount{
-      player_find:str_no,
-      tournamentid:str_tournament_id,
-      player:participant.key.to_bytes(),
-      opponent:opp,
-      level:0,
-      playerno_int:t_counter.player_participating,
-      opponent_played_on:t.starts_at,
-      is_playing:0,
-      waiting_opponent_to_join:0
-    };
+    if state.initializer_reveal == 0 || state.guest_reveal == 0 {
+      state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+      return Ok(());
+    }
 
-    let full: String = String::from("F");
+    let ships_total: u8 = state.fleet.iter().sum();
 
-    if t_counter.player_participating == t_counter.capacity{
-      t_counter.empty = full;
+    if state.initializer_reveal == 2 && state.guest_reveal == 2 {
+      Self::settle(&mut state, 3, RESULT_FALSE_REPORT, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
     }
+    else if state.initializer_reveal == 2 {
+      Self::settle(&mut state, 2, RESULT_FALSE_REPORT, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+    }
+    else if state.guest_reveal == 2 {
+      Self::settle(&mut state, 1, RESULT_FALSE_REPORT, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+    }
+    else {
+      let iwins: bool = state.ihits >= ships_total;
+      let gwins: bool = state.ghits >= ships_total;
 
-    t_account.serialize(&mut &mut tournament_account.data.borrow_mut()[..])?;
-    t_counter.serialize(&mut &mut counter.data.borrow_mut()[..])?;
+      if iwins && !gwins {
+        Self::settle(&mut state, 1, RESULT_SCORE, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+      }
+      else if gwins && !iwins {
+        Self::settle(&mut state, 2, RESULT_SCORE, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+      }
+      else {
+        Self::settle(&mut state, 3, RESULT_SCORE, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+      }
+    }
 
     Ok(())
   }
-  fn tournament_match_initialize(        
+  fn claim_reveal_timeout(
     accounts: &[AccountInfo],
-    program_id: &Pubkey,
-    init: Init) -> ProgramResult {
+    program_id: &Pubkey,) -> ProgramResult {
 
 
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
     let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let initializer_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let opponent_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-    let o_t_account: TournamentAccount = TournamentAccount::try_from_slice(&opponent_tour_acc.data.borrow())?;
-    let opponent_check: Pubkey = Pubkey::new_from_array(o_t_account.player);
 
-    let mut t_account: TournamentAccount = TournamentAccount::try_from_slice(&initializer_tour_acc.data.borrow())?;
-    let initializer_account_check: Pubkey = Pubkey::new_from_array(t_account.player);
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let host_check: Pubkey = Pubkey::new_from_array(state.host);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
-    let mut substract:bool = false;
-    let initializer_no: u32 = o_t_account.playerno_int;
-    let pwr: u32 = o_t_account.level as u32;
-    let mut pwrplus: u32 = o_t_account.level as u32;
-    pwrplus += 1;
-    let pwoftwo:u32 = 2;
-    let dividableby: &u32 = &pwoftwo.pow(pwrplus);
-    if initializer_no%dividableby == 0 {
-      substract = true;
-    }
-    let mut opponent_no: u32 = 0;
-    if substract == false {
-      opponent_no = &initializer_no + &pwoftwo.pow(pwr);
-    }
-    if substract == true {
-      opponent_no = &initializer_no - &pwoftwo.pow(pwr);
+    if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
+    if host.key != &host_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::AwaitingReveal {panic!()}
+
+    let current_time: u64 = Self::current_time(state.time_unit)?;
+    if current_time < state.reveal_deadline {panic!()}
+
+    //a side that never revealed forfeits, only an honest reveal on the other side takes the wager
+    if state.initializer_reveal == 1 && state.guest_reveal == 0 {
+      Self::settle(&mut state, 1, RESULT_REVEAL_TIMEOUT, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
     }
-    let mut game_seed: String = String::new();
-    let opponent_no_str: &String = &opponent_no.to_string();
-    let initializer_no_str: &String = &initializer_no.to_string();
-    let seed: String = String::from("v");
-    if opponent_no > initializer_no{
-      game_seed += initializer_no_str;
-      game_seed += &seed;
-      game_seed += opponent_no_str;
+    else if state.guest_reveal == 1 && state.initializer_reveal == 0 {
+      Self::settle(&mut state, 2, RESULT_REVEAL_TIMEOUT, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
     }
-    if opponent_no < initializer_no{
-      game_seed += opponent_no_str;
-      game_seed += &seed;
-      game_seed += initializer_no_str;
+    else {
+      Self::settle(&mut state, 3, RESULT_REVEAL_TIMEOUT, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
     }
 
-    let mut game_id: String = String::new();
+    Ok(())
+  }
+  fn claim_victory_by_time(        
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,) -> ProgramResult {
 
-    game_id += &t.tournament_id;
-    game_id += &game_seed;
 
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-    let game_state_check: Pubkey = Pubkey::create_program_address(&[t.tournament_id.to_string().as_ref(),game_seed.as_ref(), &[init.bump]], program_id).unwrap();
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    invoke_signed(
-      &system_instruction::create_account( 
-          &initializer.key, 
-          &game_state.key,
-          t.rent,
-          140, //312???m
-          &program_id
-      ),
-      &[
-        initializer.clone(), 
-        game_state.clone(),
-      ],
-      &[&[t.tournament_id.to_string().as_ref(),game_seed.as_ref(), &[init.bump]]],
-    )?;
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-    if !initializer.is_signer{panic!()}
-    if t.is_init != 1{panic!()}
-    if t.tournament_id != t_account.tournamentid{panic!()}
-    if t.tournament_id != o_t_account.tournamentid{panic!()}
-    if o_t_account.level == t_account.level && o_t_account.waiting_opponent_to_join != 0 {panic!()}
-    if opponent.key != &opponent_check{panic!()}
-    if initializer.key != &initializer_account_check{panic!()}
-    if game_state.key != &game_state_check{panic!()}
-    if tournament.owner != program_id{panic!()}
-    if game_state.owner != program_id{panic!()}
-    if opponent_tour_acc.owner != program_id{panic!()}
-    if initializer_tour_acc.owner != program_id{panic!()}
-    if tournament.is_writable {panic!()}
 
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let host_check: Pubkey = Pubkey::new_from_array(state.host);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
-    let mut state: TGameState = TGameState::try_from_slice(&game_state.data.borrow())?;
+    if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
+    if host.key != &host_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::Active && state.status != GameStatus::BetweenGames {panic!()}
 
+    let current_time: u64 = Self::current_time(state.time_unit)?;
 
-    if state.initialized != 0 {panic!()}
-    if init.initializer_game_hash.len() != 44 {panic!()}///////////////////////////////////////////
+    let time_passed: u64 = &current_time - &state.lastplaytime;
 
-    state.game_id=game_id;
-    state.lamports=0;
-    state.initializer= initializer_tour_acc.key.to_bytes();
-    state.initializergamehash= init.initializer_game_hash;
-    state.guest= [0;32];
-    state.guestgamehash= [0;32];
-    state.initialized = 3;
+    if time_passed<state.move_timeout{panic!()}
 
+    if state.status == GameStatus::BetweenGames { // whoever didn't commit the next series game forfeits the series
+      if state.series_commits == 1 {
+        Self::settle_wager(&mut state, 1, RESULT_SERIES_FORFEIT, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+      }
+      else if state.series_commits == 2 {
+        Self::settle_wager(&mut state, 2, RESULT_SERIES_FORFEIT, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+      }
+      else if state.series_commits == 0 { // nobody came back, both stakes are returned as in a draw
+        Self::settle_wager(&mut state, 3, RESULT_SERIES_FORFEIT, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+      }
+      else {panic!()}
+      return Ok(());
+    }
 
-    t_account.opponent = opponent_no;
-    t_account.waiting_opponent_to_join = 1;
+    let mut gwins:bool=false;
+    let mut iwins:bool=false;
 
-    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
-    t_account.serialize(&mut &mut initializer_tour_acc.data.borrow_mut()[..])?;
+    if state.whoseturn == 1{
+      gwins = true;
+    }
+    if state.whoseturn == 2{
+      iwins = true;
+    }
+    if iwins {
+      Self::settle(&mut state, 1, RESULT_TIME, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+    }
+    if gwins {
+      Self::settle(&mut state, 2, RESULT_TIME, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+    }
 
     Ok(())
   }
-  fn tournament_match_accept(
+  fn claim_victory_by_flag_fall(
     accounts: &[AccountInfo],
-    program_id: &Pubkey,
-    join: Join) -> ProgramResult {
+    program_id: &Pubkey,) -> ProgramResult {
 
 
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
     let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let initializer_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let opponent_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-
-    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
-
-    let mut o_t_account: TournamentAccount = TournamentAccount::try_from_slice(&opponent_tour_acc.data.borrow())?;
-    let opponent_check: Pubkey = Pubkey::new_from_array(o_t_account.player);
-
-    let mut t_account: TournamentAccount = TournamentAccount::try_from_slice(&initializer_tour_acc.data.borrow())?;
-    let initializer_account_check: Pubkey = Pubkey::new_from_array(t_account.player);
-
-        if opponent.key != &opponent_check{panic!()}
-    if initializer.key != &initializer_account_check{panic!()}
-
-    let mut state: TGameState = TGameState::try_from_slice(&game_state.data.borrow())?;
-
 
-    if !opponent.is_signer{panic!()}
-    if t.is_init != 1{panic!()}
-    if t.tournament_id != t_account.tournamentid{panic!()}
-    if t.tournament_id != o_t_account.tournamentid{panic!()}
-    if o_t_account.level == t_account.level {panic!()}
-    if o_t_account.playerno_int == t_account.opponent {panic!()}
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-    if state.initializer != t_account.player {panic!()}
-    if tournament.owner != program_id{panic!()}
-    if opponent_tour_acc.owner != program_id{panic!()}
-    if initializer_tour_acc.owner != program_id{panic!()}
-    if game_state.owner != program_id{panic!()}
-    if state.initialized != 3 {panic!()}
-    if tournament.is_writable {panic!()}
 
-    if join.guest_game_hash.len() != 44 {panic!()}
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let host_check: Pubkey = Pubkey::new_from_array(state.host);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
-    state.guest = opponent.key.to_bytes();
-    state.guestgamehash = join.guest_game_hash;
-    state.initialized = 4;
-    state.whoseturn = 1;
+    if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
+    if host.key != &host_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::Active {panic!()}
+    if state.time_bank == 0 {panic!()}
 
-    let clock: Clock= Clock::get()?;
-    let current_time: u64 = clock.unix_timestamp as u64;
+    let current_time: u64 = Self::current_time(state.time_unit)?;
 
-    o_t_account.is_playing = 1;
-    t_account.opponent_played_on = current_time;
-    t_account.is_playing = 1;
-    t_account.waiting_opponent_to_join = 0;
+    let time_passed: u64 = current_time - state.lastplaytime;
 
-    o_t_account.serialize(&mut &mut opponent_tour_acc.data.borrow_mut()[..])?;
-    t_account.serialize(&mut &mut initializer_tour_acc.data.borrow_mut()[..])?;
-    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+    if state.whoseturn == 1{
+      if time_passed < state.initializer_time_bank {panic!()}
+      Self::settle(&mut state, 2, RESULT_FLAG_FALL, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+    }
+    if state.whoseturn == 2{
+      if time_passed < state.guest_time_bank {panic!()}
+      Self::settle(&mut state, 1, RESULT_FLAG_FALL, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+    }
 
     Ok(())
   }
-  fn tournament_play(        
-    program_id: &Pubkey,
+  fn resign(
     accounts: &[AccountInfo],
-    m: Move,) -> ProgramResult {
-    
+    program_id: &Pubkey,) -> ProgramResult {
+
 
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
     let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let initializer_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let opponent_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    let mut state: TGameState = TGameState::try_from_slice(&game_state.data.borrow())?;
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
 
-    let opponent_tournament_account: TournamentAccount = TournamentAccount::try_from_slice(&opponent_tour_acc.data.borrow())?;
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let host_check: Pubkey = Pubkey::new_from_array(state.host);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
-    let opponent_check: Pubkey = Pubkey::new_from_array(opponent_tournament_account.player);
+    if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
+    if host.key != &host_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::Active {panic!()}
+    if initializer.is_signer && guest.is_signer {panic!()}
 
-    let initializer_tournament_account: TournamentAccount = TournamentAccount::try_from_slice(&initializer_tour_acc.data.borrow())?;
+    if initializer.is_signer{
+      Self::settle(&mut state, 2, RESULT_RESIGN, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+    }
+    else if guest.is_signer{
+      Self::settle(&mut state, 1, RESULT_RESIGN, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
+    }
+    else {panic!()}
 
-    let initializer_account_check: Pubkey = Pubkey::new_from_array(initializer_tournament_account.player);
+    Ok(())
+  }
+  fn offer_draw(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,) -> ProgramResult {
 
-    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
 
-    if t.is_init != 1{panic!()}
-    if t.tournament_id != initializer_tournament_account.tournamentid{panic!()}
-    if t.tournament_id != opponent_tournament_account.tournamentid{panic!()}
-    if state.initializer != initializer_tournament_account.player{panic!()}
-    if state.guest != opponent_tournament_account.player{panic!()}
-    if opponent_tournament_account.level == initializer_tournament_account.level {panic!()}
-    if opponent_tournament_account.playerno_int == initializer_tournament_account.opponent {panic!()}
-    if opponent_tournament_account.opponent == initializer_tournament_account.playerno_int {panic!()}
-    if opponent.key != &opponent_check{panic!()}
-    if initializer.key != &initializer_account_check{panic!()}
-    if initializer.key != &initializer_check{panic!()}
-    if tournament.owner != program_id{panic!()}
-    if opponent_tour_acc.owner != program_id{panic!()}
-    if initializer_tour_acc.owner != program_id{panic!()}
-    if game_state.owner != program_id{panic!()}
-    if tournament.is_writable {panic!()}
-    if opponent_tournament_account.is_playing != 1{panic!()}
-    if initializer_tournament_account.is_playing != 1{panic!()}
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-    
-    if state.initialized != 4 {panic!()}
-    if m.mymove > 110 {panic!()}
-    if m.mymove < 11 {panic!()}
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    let clock: Clock= Clock::get()?;
-    let current_time: u64 = clock.unix_timestamp as u64;
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-    let mut turn:u8 = 0;
-    let hit:u8 = m.opponents_last_move_is_hit;
-    let index: usize = m.mymove as usize;
-    let index2: usize = state.lastmove as usize;
 
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
-    if state.whoseturn == 1{ // 1 initializer
-      if !initializer.is_signer{panic!()};
-      turn = 2;
-      state.ishots += 1;
-      state.ghits += hit;
-      if state.guest_board_state[index] != 0{panic!()};
-      state.guest_board_state[index] += 1;
-      state.initializer_board_state[index2] += hit;
-    }//hit 2
-     //miss 1
-     //unchecked 0
+    if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::Active {panic!()}
+    if initializer.is_signer && guest.is_signer {panic!()}
 
-    if state.whoseturn == 2{ // 2 guest
-      if !opponent.is_signer{panic!()};
-      turn = 1;
-      state.gshots += 1;
-      state.ihits += hit;
-      if state.initializer_board_state[index] != 0{panic!()};
-      state.initializer_board_state[index] += 1;
-      state.guest_board_state[index2] += hit;
+    if initializer.is_signer{
+      state.draw_offer = 1;
     }
-
-    state.lastplaytime = current_time;
-    state.whoseturn  = turn;
-    state.lastmove = m.mymove;
-
+    else if guest.is_signer{
+      state.draw_offer = 2;
+    }
+    else {panic!()}
 
     state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
 
     Ok(())
   }
-  fn tournament_claim_victory_by_score(        
-    program_id: &Pubkey,
+  fn accept_draw(
     accounts: &[AccountInfo],
-    claim: ClaimVictory,) -> ProgramResult {
-    
+    program_id: &Pubkey,) -> ProgramResult {
+
 
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
     let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let initializer_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let opponent_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    let mut state: TGameState = TGameState::try_from_slice(&game_state.data.borrow())?;
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
 
-    let mut o_t_account: TournamentAccount = TournamentAccount::try_from_slice(&opponent_tour_acc.data.borrow())?;
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let host_check: Pubkey = Pubkey::new_from_array(state.host);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
-    let opponent_check: Pubkey = Pubkey::new_from_array(o_t_account.player);
+    if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
+    if host.key != &host_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::Active {panic!()}
 
-    let mut t_account: TournamentAccount = TournamentAccount::try_from_slice(&initializer_tour_acc.data.borrow())?;
+    if state.draw_offer == 1{
+      if !guest.is_signer{panic!()}
+    }
+    else if state.draw_offer == 2{
+      if !initializer.is_signer{panic!()}
+    }
+    else {panic!()}
 
-    let initializer_account_check: Pubkey = Pubkey::new_from_array(t_account.player);
+    Self::settle(&mut state, 3, RESULT_DRAW_AGREED, SettleAccounts{game_state, initializer, guest, host, remaining: accounts_iter.as_slice()})?;
 
-    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    Ok(())
+  }
+  fn close_game(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,) -> ProgramResult {
 
-    if t.is_init != 1{panic!()}
-    if t.tournament_id != t_account.tournamentid{panic!()}
-    if t.tournament_id != o_t_account.tournamentid{panic!()}
-    if state.initializer != t_account.player{panic!()}
-    if state.guest != o_t_account.player{panic!()}
-    if o_t_account.level == t_account.level {panic!()}
-    if o_t_account.playerno_int == t_account.opponent {panic!()}
-    if o_t_account.opponent == t_account.playerno_int {panic!()}
-    if opponent.key != &opponent_check{panic!()}
-    if initializer.key != &initializer_account_check{panic!()}
-    if initializer.key != &initializer_check{panic!()}
-    if tournament.owner != program_id{panic!()}
-    if opponent_tour_acc.owner != program_id{panic!()}
-    if initializer_tour_acc.owner != program_id{panic!()}
-    if game_state.owner != program_id{panic!()}
-    if tournament.is_writable {panic!()}
-    if o_t_account.is_playing != 1{panic!()}
-    if t_account.is_playing != 1{panic!()}
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-    
-    if state.initialized != 4 {panic!()}
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-    let clock: Clock= Clock::get()?;
-    let current_time: u64 = clock.unix_timestamp as u64;
 
-    let mut game_arr:[u8;17]=[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0];
-
-    if claim.s1ax == 1{
-      if claim.s1cox > 6 {panic!()}
-      if claim.s1cox < 1 {panic!()}
-      if claim.s1coy > 10 {panic!()}
-      if claim.s1coy < 1 {panic!()}
-      game_arr[0] = claim.s1cox+(claim.s1coy*10);
-      game_arr[1] = (claim.s1cox + 1)+(claim.s1coy*10);
-      game_arr[2] = (claim.s1cox + 2)+(claim.s1coy*10);
-      game_arr[3] = (claim.s1cox + 3)+(claim.s1coy*10);
-      game_arr[4] = (claim.s1cox + 4)+(claim.s1coy*10);
-    }
-    if claim.s1ax == 2{
-      if claim.s1coy > 6 {panic!()}
-      if claim.s1coy < 1 {panic!()}
-      if claim.s1cox > 10 {panic!()}
-      if claim.s1cox < 1 {panic!()}
-      game_arr[0] = (claim.s1coy*10)+(claim.s1cox);
-      game_arr[1] = ((claim.s1coy + 1)*10)+(claim.s1cox);
-      game_arr[2] = ((claim.s1coy + 2)*10)+(claim.s1cox);
-      game_arr[3] = ((claim.s1coy + 3)*10)+(claim.s1cox);
-      game_arr[4] = ((claim.s1coy + 4)*10)+(claim.s1cox);
-    }
-    if claim.s2ax == 1{
-      if claim.s2cox > 6 {panic!()}
-      if claim.s2cox < 1 {panic!()}
-      if claim.s2coy > 10 {panic!()}
-      if claim.s2coy < 1 {panic!()}
-      game_arr[5] = claim.s2cox+(claim.s2coy*10);
-      game_arr[6] = (claim.s2cox + 1)+(claim.s2coy*10);
-      game_arr[7] = (claim.s2cox + 2)+(claim.s2coy*10);
-      game_arr[8] = (claim.s2cox + 3)+(claim.s2coy*10);
-    }
-    if claim.s2ax == 2{
-      if claim.s2coy > 6 {panic!()}
-      if claim.s2coy < 1 {panic!()}
-      if claim.s2cox > 10 {panic!()}
-      if claim.s2cox < 1 {panic!()}
-      game_arr[5] = (claim.s2coy*10)+(claim.s2cox);
-      game_arr[6] = ((claim.s2coy + 1)*10)+(claim.s2cox);
-      game_arr[7] = ((claim.s2coy + 2)*10)+(claim.s2cox);
-      game_arr[8] = ((claim.s2coy + 3)*10)+(claim.s2cox);
-    }
-    if claim.s3ax == 1{
-      if claim.s3cox > 6 {panic!()}
-      if claim.s3cox < 1 {panic!()}
-      if claim.s3coy > 10 {panic!()}
-      if claim.s3coy < 1 {panic!()}
-      game_arr[9] = claim.s3cox+(claim.s3coy*10);
-      game_arr[10] = (claim.s3cox + 1)+(claim.s3coy*10);
-      game_arr[11] = (claim.s3cox + 2)+(claim.s3coy*10);
-    }
-    if claim.s3ax == 2{
-      if claim.s3coy > 6 {panic!()}
-      if claim.s3coy < 1 {panic!()}
-      if claim.s3cox > 10 {panic!()}
-      if claim.s3cox < 1 {panic!()}
-      game_arr[9] = (claim.s3coy*10)+(claim.s3cox);
-      game_arr[10] = ((claim.s3coy + 1)*10)+(claim.s3cox);
-      game_arr[11] = ((claim.s3coy + 2)*10)+(claim.s3cox);
-    }
-    if claim.s4ax == 1{
-      if claim.s4cox > 6 {panic!()}
-      if claim.s4cox < 1 {panic!()}
-      if claim.s4coy > 10 {panic!()}
-      if claim.s4coy < 1 {panic!()}
-      game_arr[12] = claim.s4cox+(claim.s4coy*10);
-      game_arr[13] = (claim.s4cox + 1)+(claim.s4coy*10);
-      game_arr[14] = (claim.s4cox + 2)+(claim.s4coy*10);
-    }
-    if claim.s4ax == 2{
-      if claim.s4coy > 6 {panic!()}
-      if claim.s4coy < 1 {panic!()}
-      if claim.s4cox > 10 {panic!()}
-      if claim.s4cox < 1 {panic!()}
-      game_arr[12] = (claim.s4coy*10)+(claim.s4cox);
-      game_arr[13] = ((claim.s4coy + 1)*10)+(claim.s4cox);
-      game_arr[14] = ((claim.s4coy + 2)*10)+(claim.s4cox);
-    }
-    if claim.s5ax == 1{
-      if claim.s5cox > 6 {panic!()}
-      if claim.s5cox < 1 {panic!()}
-      if claim.s5coy > 10 {panic!()}
-      if claim.s5coy < 1 {panic!()}
-      game_arr[15] = claim.s5cox+(claim.s5coy*10);
-      game_arr[16] = (claim.s5cox + 1)+(claim.s5coy*10);
-    }
-    if claim.s5ax == 2{
-      if claim.s5coy > 6 {panic!()}
-      if claim.s5coy < 1 {panic!()}
-      if claim.s5cox > 10 {panic!()}
-      if claim.s5cox < 1 {panic!()}
-      game_arr[15] = (claim.s5coy*10)+(claim.s5cox);
-      game_arr[16] = ((claim.s5coy + 1)*10)+(claim.s5cox);
-    }
-
-    let game_hash: keccak::Hash = keccak::hashv(&[&claim.seed.to_string().as_bytes(),&game_arr,&claim.seed.to_string().as_bytes()]);
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
+    if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if !initializer.is_signer{panic!()}
+    if !state.status.is_finished() && state.status != GameStatus::Aborted {panic!()}
 
-    if claim.claims == 1 {
-      if state.initializergamehash != game_hash.0{panic!()}
-    }
-    else {
-      if state.guestgamehash != game_hash.0{panic!()}
-    }
+    //an aborted game already closed its escrow
+    if state.mint == [0;32] && state.status != GameStatus::Aborted {
+      let escrow: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let stake: u64 = state.lamports;
+      let held: u64 = if state.rematch_offer != 0 {stake} else {0};
+      Self::check_escrow(state.escrow_bump, state.escrowed, held, game_state, escrow, program_id)?;
 
-    if claim.claims == 1 {
-      for x in 1..18 {
-        let u: usize = x as usize;
-        let s: usize = game_arr[u] as usize;
-        if state.initializer_board_state[s] > 0 && state.initializer_board_state[s] != 2 {
-          state.ihits = 0;
-          state.ghits = 17;
-        }
+      if state.rematch_offer == 2{
+        Self::escrow_out(&mut state.escrowed, escrow, guest, stake)?;
       }
-    }else{
-      for x in 1..18 {
-        let u: usize = x as usize;
-        let s: usize = game_arr[u] as usize;
-        if state.guest_board_state[s] > 0 && state.guest_board_state[s] != 2 {
-          state.ihits = 17;
-          state.ghits = 0;
-        }
+      if state.rematch_offer == 1{
+        Self::escrow_out(&mut state.escrowed, escrow, initializer, stake)?;
       }
+      Self::close_escrow(state.escrowed, escrow, initializer)?;
     }
 
+    game_state.data.borrow_mut().fill(0);
 
-    let mut draw:bool=false;
-    let mut iwins:bool=false;
-    let mut gwins:bool=false;
+    let value: u64 = **game_state.lamports.borrow();
 
-    let mut the_no = 0;
-    if t_account.playerno_int>o_t_account.playerno_int{
-      the_no = t_account.playerno_int;
-    }
-    if t_account.playerno_int<o_t_account.playerno_int{
-      the_no = o_t_account.playerno_int;
-    }
+    **game_state.lamports.borrow_mut()-= value;
+    **initializer.lamports.borrow_mut()+= value;
 
-    if state.ishots != state.gshots{panic!()}
-    if state.ihits == 17{iwins = true;}
-    if state.ghits == 17{gwins = true;}
-    if gwins && iwins{draw = true;iwins = false;gwins = false;}
+    Ok(())
+  }
+  fn propose_rematch(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    rematch: Rematch) -> ProgramResult {
 
-    if iwins == true{
 
-      let us: usize = o_t_account.level as usize;
-      let multiply: u64 = t.lvl_get[us] as u64; 
-      let reward:u64 = multiply*t.entrance_fee;
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-      let multiply: u64 = t.host_get as u64;
-      let host_fee: u64 = (t.entrance_fee/100)*multiply;
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let escrow: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-      **opponent_tour_acc.lamports.borrow_mut()-= host_fee;
-      **host.lamports.borrow_mut()+= host_fee;
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-      **opponent_tour_acc.lamports.borrow_mut()-= reward;
-      **opponent.lamports.borrow_mut()+= reward;
 
-      let value: u64 = **opponent_tour_acc.lamports.borrow();
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
-      **opponent_tour_acc.lamports.borrow_mut()-= value;
-      **initializer_tour_acc.lamports.borrow_mut()+= value;
+    if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if !state.status.is_finished() {panic!()}
+
+    Self::check_escrow(state.escrow_bump, state.escrowed, 0, game_state, escrow, program_id)?;
+    if state.rematch_offer != 0 {panic!()}
+    if state.mint != [0;32] {panic!()}
+    if initializer.is_signer && guest.is_signer {panic!()}
+    if !rematch.ship_hashes.is_empty() && rematch.ship_hashes.len() != state.fleet.len() {panic!()}
+
+    let mut proposer: &AccountInfo<'_> = initializer;
+
+    if initializer.is_signer{
+      state.rematch_offer = 1;
+      state.initializergamehash = rematch.game_hash;
+      state.initializer_board_root = rematch.board_root;
+      state.initializer_ship_hash_count = rematch.ship_hashes.len() as u8;
+      state.initializer_ship_hashes = Self::ship_hash_buffer(&rematch.ship_hashes);
+    }
+    else if guest.is_signer{
+      proposer = guest;
+      state.rematch_offer = 2;
+      state.guestgamehash = rematch.game_hash;
+      state.guest_board_root = rematch.board_root;
+      state.guest_ship_hash_count = rematch.ship_hashes.len() as u8;
+      state.guest_ship_hashes = Self::ship_hash_buffer(&rematch.ship_hashes);
+    }
+    else {panic!()}
 
-      let game: u64 = **game_state.lamports.borrow();
+    state.lamports = rematch.lamports;
 
-      **game_state.lamports.borrow_mut()-= game;
-      **initializer_tour_acc.lamports.borrow_mut()+= game;
+    Self::escrow_in(&mut state.escrowed, proposer, escrow, rematch.lamports)?;
 
-      let str_player_no: String = the_no.to_string();
-      let mut somestr: String = String::from("pppppp");
-      let revstr: String = str_player_no.chars().rev().collect::<String>();
-      let offset: &usize = &revstr.len();
-      somestr.replace_range(..offset, &revstr);
-      let offset2: usize = somestr.len();
-      let mut find_me: String = t_account.player_find;
-      find_me.replace_range(..offset2,&somestr);
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
 
-      t_account.player_find = find_me;
-      t_account.is_playing = 0;
-      t_account.waiting_opponent_to_join = 0;
-      t_account.level += 1;
-      t_account.playerno_int = the_no;
-      t_account.opponent_played_on = current_time;
+    Ok(())
+  }
+  fn accept_rematch(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    rematch: Rematch) -> ProgramResult {
 
-      t_account.serialize(&mut &mut initializer_tour_acc.data.borrow_mut()[..])?;
 
-    }
-    if gwins == true{
-      let us: usize = t_account.level as usize;
-      let multiply: u64 = t.lvl_get[us] as u64; 
-      let reward:u64 = multiply*t.entrance_fee;
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-      let multiply: u64 = t.host_get as u64;
-      let host_fee: u64 = (t.entrance_fee/100)*multiply;
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let escrow: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-      **initializer_tour_acc.lamports.borrow_mut()-= host_fee;
-      **host.lamports.borrow_mut()+= host_fee;
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-      **initializer_tour_acc.lamports.borrow_mut()-= reward;
-      **initializer.lamports.borrow_mut()+= reward;
 
-      let value: u64 = **initializer_tour_acc.lamports.borrow();
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
-      **initializer_tour_acc.lamports.borrow_mut()-= value;
-      **opponent_tour_acc.lamports.borrow_mut()+= value;
+    if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if !state.status.is_finished() {panic!()}
+
+    Self::check_escrow(state.escrow_bump, state.escrowed, state.lamports, game_state, escrow, program_id)?;
+    if rematch.lamports != state.lamports {panic!()}
+
+    let mut accepter: &AccountInfo<'_> = initializer;
+
+    if state.rematch_offer == 1{
+      if !guest.is_signer{panic!()}
+      if (state.initializer_board_root == [0;32]) != (rematch.board_root == [0;32]) {panic!()}
+      if state.initializer_ship_hash_count as usize != rematch.ship_hashes.len() {panic!()}
+      accepter = guest;
+      state.guestgamehash = rematch.game_hash;
+      state.guest_board_root = rematch.board_root;
+      state.guest_ship_hash_count = rematch.ship_hashes.len() as u8;
+      state.guest_ship_hashes = Self::ship_hash_buffer(&rematch.ship_hashes);
+    }
+    else if state.rematch_offer == 2{
+      if !initializer.is_signer{panic!()}
+      if (state.guest_board_root == [0;32]) != (rematch.board_root == [0;32]) {panic!()}
+      if state.guest_ship_hash_count as usize != rematch.ship_hashes.len() {panic!()}
+      state.initializergamehash = rematch.game_hash;
+      state.initializer_board_root = rematch.board_root;
+      state.initializer_ship_hash_count = rematch.ship_hashes.len() as u8;
+      state.initializer_ship_hashes = Self::ship_hash_buffer(&rematch.ship_hashes);
+    }
+    else {panic!()}
 
-      let game: u64 = **game_state.lamports.borrow();
+    Self::escrow_in(&mut state.escrowed, accepter, escrow, rematch.lamports)?;
 
-      **game_state.lamports.borrow_mut()-= game;
-      **initializer_tour_acc.lamports.borrow_mut()+= game;
+    let current_time: u64 = Self::current_time(state.time_unit)?;
 
-      let str_player_no: String = the_no.to_string();
-      let mut somestr: String = String::from("pppppp");
-      let revstr: String = str_player_no.chars().rev().collect::<String>();
-      let offset: &usize = &revstr.len();
-      somestr.replace_range(..offset, &revstr);
-      let offset2: usize = somestr.len();
-      let mut find_me: String = o_t_account.player_find;
-      find_me.replace_range(..offset2,&somestr);
+    Self::reset_game(&mut state, current_time)?;
+    state.initializer_series_wins = 0;
+    state.guest_series_wins = 0;
+    state.series_games = 0;
+    state.rematch_offer = 0;
 
-      o_t_account.player_find = find_me;
-      o_t_account.is_playing = 0;
-      o_t_account.waiting_opponent_to_join = 0;
-      o_t_account.level += 1;
-      o_t_account.playerno_int = the_no;
-      o_t_account.opponent_played_on = current_time;
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
 
-      o_t_account.serialize(&mut &mut opponent_tour_acc.data.borrow_mut()[..])?;
+    Ok(())
+  }
+  fn commit_series_game(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    game: SeriesGame) -> ProgramResult {
 
-    }
-    if draw == true{
 
-      o_t_account.is_playing = 0;
-      o_t_account.waiting_opponent_to_join = 0;
-      o_t_account.opponent_played_on = current_time;
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-      t_account.is_playing = 0;
-      t_account.waiting_opponent_to_join = 0;
-      t_account.opponent_played_on = current_time;
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-      let value: u64 = **game_state.lamports.borrow();
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-      **game_state.lamports.borrow_mut()-= value;
-      **initializer.lamports.borrow_mut()+= value;
 
-      t_account.serialize(&mut &mut initializer_tour_acc.data.borrow_mut()[..])?;
-      o_t_account.serialize(&mut &mut opponent_tour_acc.data.borrow_mut()[..])?;
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
+    if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::BetweenGames {panic!()}
+    if initializer.is_signer && guest.is_signer {panic!()}
+
+    if initializer.is_signer{
+      if state.series_commits & 1 != 0 {panic!()}
+      if (state.initializer_board_root == [0;32]) != (game.board_root == [0;32]) {panic!()}
+      if state.initializer_ship_hash_count as usize != game.ship_hashes.len() {panic!()}
+      state.initializergamehash = game.game_hash;
+      state.initializer_board_root = game.board_root;
+      state.initializer_ship_hash_count = game.ship_hashes.len() as u8;
+      state.initializer_ship_hashes = Self::ship_hash_buffer(&game.ship_hashes);
+      state.series_commits |= 1;
     }
-    
+    else if guest.is_signer{
+      if state.series_commits & 2 != 0 {panic!()}
+      if (state.guest_board_root == [0;32]) != (game.board_root == [0;32]) {panic!()}
+      if state.guest_ship_hash_count as usize != game.ship_hashes.len() {panic!()}
+      state.guestgamehash = game.game_hash;
+      state.guest_board_root = game.board_root;
+      state.guest_ship_hash_count = game.ship_hashes.len() as u8;
+      state.guest_ship_hashes = Self::ship_hash_buffer(&game.ship_hashes);
+      state.series_commits |= 2;
+    }
+    else {panic!()}
+
+    let current_time: u64 = Self::current_time(state.time_unit)?;
+
+    if state.series_commits == 3 {
+      Self::reset_game(&mut state, current_time)?;
+    }
+    else {
+      state.lastplaytime = current_time;
+    }
+
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+
     Ok(())
   }
-  fn eliminate_player_who_hasnt_initialized_his_game(
+  fn cancel_rematch(
     accounts: &[AccountInfo],
     program_id: &Pubkey,) -> ProgramResult {
 
 
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-    let eliminate: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let eliminate_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let opponent_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-
-    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
-
-    let el_t_account: TournamentAccount = TournamentAccount::try_from_slice(&eliminate_tour_acc.data.borrow())?;
-
-    let el_check: Pubkey = Pubkey::new_from_array(el_t_account.player);
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let guest: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let escrow: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    let mut op_t_account: TournamentAccount = TournamentAccount::try_from_slice(&opponent_tour_acc.data.borrow())?;
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-    let opponent_check: Pubkey = Pubkey::new_from_array(op_t_account.player);
 
+    let guest_check: Pubkey = Pubkey::new_from_array(state.guest);
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
-    if t.is_init != 1{panic!()}
-    if t.tournament_id != el_t_account.tournamentid{panic!()}
-    if t.tournament_id != op_t_account.tournamentid{panic!()}
-    if eliminate.key != &el_check{panic!()}
-    if opponent.key != &opponent_check{panic!()}
-    if tournament.owner != program_id{panic!()}
-    if el_t_account.waiting_opponent_to_join != 0 {panic!()}
-    if eliminate_tour_acc.owner != program_id{panic!()}
-    if tournament.is_writable {panic!()}
-    if el_t_account.is_playing != 0  {panic!()}
-    if el_t_account.opponent != op_t_account.playerno_int{panic!()}
-    if el_t_account.playerno_int != op_t_account.opponent{panic!()}
-    if el_t_account.level != op_t_account.level{panic!()}
+    if initializer.key != &initializer_check{panic!()}
+    if guest.key != &guest_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if !state.status.is_finished() {panic!()}
 
-    let clock: Clock= Clock::get()?;
-    let current_time: u64 = clock.unix_timestamp as u64;
-    let time_passed: u64 = current_time - el_t_account.opponent_played_on;
+    Self::check_escrow(state.escrow_bump, state.escrowed, state.lamports, game_state, escrow, program_id)?;
 
-    if time_passed < t.time_is_up {panic!()}
+    let stake: u64 = state.lamports;
 
-    let mut the_no: u32 = 0;
-    if el_t_account.playerno_int>op_t_account.playerno_int{
-      the_no = el_t_account.playerno_int;
+    if state.rematch_offer == 1{
+      if !initializer.is_signer{panic!()}
+      Self::escrow_out(&mut state.escrowed, escrow, initializer, stake)?;
     }
-    if el_t_account.playerno_int<op_t_account.playerno_int{
-      the_no = op_t_account.playerno_int;
+    else if state.rematch_offer == 2{
+      if !guest.is_signer{panic!()}
+      Self::escrow_out(&mut state.escrowed, escrow, guest, stake)?;
     }
+    else {panic!()}
 
-    let us: usize = el_t_account.level as usize;
-    let multiply: u64 = t.lvl_get[us] as u64;
-    let reward:u64 = multiply*t.entrance_fee;
+    state.rematch_offer = 0;
 
-    let multiply: u64 = t.host_get as u64;
-    let host_fee: u64 = (t.entrance_fee/100)*multiply;
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
 
-    **eliminate_tour_acc.lamports.borrow_mut()-= host_fee;
-    **host.lamports.borrow_mut()+= host_fee;
+    Ok(())
+  }
+  fn abort_game(        
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,) -> ProgramResult {
 
-    **eliminate_tour_acc.lamports.borrow_mut()-= reward;
-    **eliminate.lamports.borrow_mut()+= reward;
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-    let value: u64 = **eliminate_tour_acc.lamports.borrow();
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-    **eliminate_tour_acc.lamports.borrow_mut()-= value;
-    **opponent_tour_acc.lamports.borrow_mut()+= value;
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-    let str_player_no: String = the_no.to_string();
-    let mut somestr: String = String::from("pppppp");
-    let revstr: String = str_player_no.chars().rev().collect::<String>();
-    let offset: &usize = &revstr.len();
-    somestr.replace_range(..offset, &revstr);
-    let offset2: usize = somestr.len();
-    let mut find_me: String = op_t_account.player_find;
-    find_me.replace_range(..offset2,&somestr);
 
-    op_t_account.player_find = find_me;
-    op_t_account.is_playing = 0;
-    op_t_account.waiting_opponent_to_join = 0;
-    op_t_account.level += 1;
-    op_t_account.playerno_int = the_no;
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
-    op_t_account.serialize(&mut &mut opponent_tour_acc.data.borrow_mut()[..])?;
+    if initializer.key != &initializer_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::Open {panic!()}
 
+    Self::record_result(&mut state, 0, RESULT_ABORT)?;
+    Self::transition(&mut state.status, GameStatus::Aborted)?;
 
-    Ok(())
+    if state.mint != [0;32] {
+      Self::refund_vault(&state, game_state, initializer, accounts_iter.as_slice(), program_id)?;
+    }
+    else {
+      Self::refund_escrow(&mut state, game_state, initializer, accounts_iter.as_slice(), program_id)?;
+    }
+
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+
+    Self::close_or_keep(&state, game_state, initializer)
   }
-  fn eliminate_player_who_hasnt_made_his_move(
+  fn expire_game(
     accounts: &[AccountInfo],
     program_id: &Pubkey,) -> ProgramResult {
 
-
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
+    let cranker: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let initializer_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let opponent_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
     let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-
-    let state: TGameState = TGameState::try_from_slice(&game_state.data.borrow())?;
-
-    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
-
-    let mut opponent_tournament_account: TournamentAccount = TournamentAccount::try_from_slice(&opponent_tour_acc.data.borrow())?;
-
-    let opponent_check: Pubkey = Pubkey::new_from_array(opponent_tournament_account.player);
 
-    let mut initializer_tournament_account: TournamentAccount = TournamentAccount::try_from_slice(&initializer_tour_acc.data.borrow())?;
+    let mut state: GameState = GameState::try_from_slice(&game_state.data.borrow())?;
 
-    let initializer_account_check: Pubkey = Pubkey::new_from_array(initializer_tournament_account.player);
 
     let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
 
-    if t.is_init != 1{panic!()}
-    if t.tournament_id != initializer_tournament_account.tournamentid{panic!()}
-    if t.tournament_id != opponent_tournament_account.tournamentid{panic!()}
-    if state.initializer != initializer_tournament_account.player{panic!()}
-    if state.guest != opponent_tournament_account.player{panic!()}
-    if opponent_tournament_account.level == initializer_tournament_account.level {panic!()}
-    if opponent_tournament_account.opponent == initializer_tournament_account.playerno_int {panic!()}
-    if opponent_tournament_account.playerno_int == initializer_tournament_account.opponent {panic!()}
-    if opponent.key != &opponent_check{panic!()}
-    if initializer.key != &initializer_account_check{panic!()}
     if initializer.key != &initializer_check{panic!()}
-    if tournament.owner != program_id{panic!()}
-    if opponent_tour_acc.owner != program_id{panic!()}
-    if initializer_tour_acc.owner != program_id{panic!()}
-    if game_state.owner != program_id{panic!()}
-    if tournament.is_writable {panic!()}
-    if opponent_tournament_account.is_playing != 1{panic!()}
-    if initializer_tournament_account.is_playing != 1{panic!()}
-    if state.initialized != 4 {panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::Open {panic!()}
+    if state.expires_at == 0 {panic!()}
 
+    let current_time: u64 = Self::current_time(state.time_unit)?;
+    if current_time < state.expires_at {panic!()}
 
-    let mut the_no: u32 = 0;
-    if initializer_tournament_account.playerno_int>opponent_tournament_account.playerno_int{
-      the_no = initializer_tournament_account.playerno_int;
+    Self::record_result(&mut state, 0, RESULT_EXPIRED)?;
+    Self::transition(&mut state.status, GameStatus::Aborted)?;
+    msg!("game {} result {:?}", game_state.key, state.result);
+
+    if state.mint != [0;32] {
+      Self::refund_vault(&state, game_state, initializer, accounts_iter.as_slice(), program_id)?;
     }
-    if initializer_tournament_account.playerno_int<opponent_tournament_account.playerno_int{
-      the_no = opponent_tournament_account.playerno_int;
+    else {
+      Self::refund_escrow(&mut state, game_state, initializer, accounts_iter.as_slice(), program_id)?;
     }
 
-    let mut iwins:bool=false;
-    let mut gwins:bool=false;
+    //the bounty comes out of the rent, the stake and the rest go back to the initializer
+    let bounty: u64 = EXPIRY_CRANK_BOUNTY.min(**game_state.lamports.borrow());
 
-    let clock: Clock= Clock::get()?;
-    let current_time: u64 = clock.unix_timestamp as u64;
+    **game_state.lamports.borrow_mut()-= bounty;
+    **cranker.lamports.borrow_mut()+= bounty;
 
+    game_state.data.borrow_mut().fill(0);
 
-    if state.whoseturn == 1{
-      let time_passed: u64 = current_time - state.lastplaytime;
-      if time_passed > t.time_is_up {gwins=true;}
-      if time_passed < t.time_is_up {panic!()}
-    }
-    if state.whoseturn == 2{
-      let time_passed: u64 = current_time - state.lastplaytime;
-      if time_passed > t.time_is_up {iwins=true;}
-      if time_passed < t.time_is_up {panic!()}
-    }
+    let value: u64 = **game_state.lamports.borrow();
 
-    if !iwins && !gwins{panic!()}
-    if !iwins && gwins{panic!()}
+    **game_state.lamports.borrow_mut()-= value;
+    **initializer.lamports.borrow_mut()+= value;
 
-    if iwins == true{
+    Ok(())
+  }
+  fn init_ffa(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    init: FfaInit) -> ProgramResult {
 
-      let us: usize = opponent_tournament_account.level as usize;
-      let multiply: u64 = t.lvl_get[us] as u64; 
-      let reward:u64 = multiply*t.entrance_fee;
 
-      let multiply: u64 = t.host_get as u64;
-      let host_fee: u64 = (t.entrance_fee/100)*multiply;
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-      **opponent_tour_acc.lamports.borrow_mut()-= host_fee;
-      **host.lamports.borrow_mut()+= host_fee;
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let host_profile: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let escrow: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-      **opponent_tour_acc.lamports.borrow_mut()-= reward;
-      **opponent.lamports.borrow_mut()+= reward;
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &init.gameseed, program_id).unwrap();
 
-      let value: u64 = **opponent_tour_acc.lamports.borrow();
+    if game_state.key != &game_state_check{panic!()}
+    if game_state.owner != program_id{panic!()}
+    if !initializer.is_signer{panic!()}
 
-      **opponent_tour_acc.lamports.borrow_mut()-= value;
-      **initializer_tour_acc.lamports.borrow_mut()+= value;
+    let config: ProgramConfig = Self::program_config(config_account, program_id)?;
+    let mut profile: HostProfile = Self::host_profile(host_profile, program_id)?;
 
-      let game: u64 = **game_state.lamports.borrow();
+    if profile.enabled != 1 {panic!()}
+    if init.host_fee_bps != profile.fee_bps {panic!()}
 
-      **game_state.lamports.borrow_mut()-= game;
-      **initializer_tour_acc.lamports.borrow_mut()+= game;
+    let mut state: FfaGameState = FfaGameState::try_from_slice(&game_state.data.borrow())?;
 
-      let str_player_no: String = the_no.to_string();
-      let mut somestr: String = String::from("pppppp");
-      let revstr: String = str_player_no.chars().rev().collect::<String>();
-      let offset: &usize = &revstr.len();
-      somestr.replace_range(..offset, &revstr);
-      let offset2: usize = somestr.len();
-      let mut find_me: String = initializer_tournament_account.player_find;
-      find_me.replace_range(..offset2,&somestr);
+    if state.status != GameStatus::Uninitialized {panic!()}
+    if init.gameseed.len() != 5 {panic!()}
+    if init.player_count < MIN_FFA_PLAYERS || init.player_count > MAX_FFA_PLAYERS {panic!()}
+    if init.no_touching > 1 {panic!()}
+    if init.move_timeout < config.min_timeout_seconds {panic!()}
+    if init.move_timeout > config.max_timeout_seconds {panic!()}
+    if init.host_fee_bps < config.min_host_fee_bps || init.host_fee_bps > config.max_host_fee_bps {panic!()}
+
+    state.host = profile.payout;
+    state.host_profile = host_profile.key.to_bytes();
+    state.lamports = init.lamports;
+    state.escrow_bump = init.escrow_bump;
+    state.escrowed = 0;
+    state.host_fee_bps = init.host_fee_bps;
+    state.player_count = init.player_count;
+    state.no_touching = init.no_touching;
+    state.board_width = Self::board_dimension(init.board_width);
+    state.board_height = Self::board_dimension(init.board_height);
+    state.fleet = Self::fleet_definition(init.fleet, state.board_width, state.board_height);
+    state.move_timeout = init.move_timeout;
+    state.joined = 1;
+    state.players = [[0;32];MAX_FFA_PLAYERS as usize];
+    state.game_hashes = [[0;32];MAX_FFA_PLAYERS as usize];
+    state.board_roots = [[0;32];MAX_FFA_PLAYERS as usize];
+    state.players[0] = initializer.key.to_bytes();
+    state.game_hashes[0] = init.game_hash;
+    state.board_roots[0] = init.board_root;
+    state.eliminated = 0;
+    state.whoseturn = 0;
+    state.winner = 0;
+    state.pending = [[0;FFA_PENDING_CAPACITY];MAX_FFA_PLAYERS as usize];
+    state.pending_len = [0;MAX_FFA_PLAYERS as usize];
+    state.shots = [0;MAX_FFA_PLAYERS as usize];
+    state.hits = [0;MAX_FFA_PLAYERS as usize];
+    state.board_states = [[0;256];MAX_FFA_PLAYERS as usize];
+    state.reveals = [0;MAX_FFA_PLAYERS as usize];
+    state.reveal_deadline = 0;
+    Self::transition(&mut state.status, GameStatus::Open)?;
+    state.gameseed = init.gameseed;
 
-      initializer_tournament_account.player_find = find_me;
-      initializer_tournament_account.is_playing = 0;
-      initializer_tournament_account.waiting_opponent_to_join = 0;
-      initializer_tournament_account.level += 1;
-      initializer_tournament_account.playerno_int = the_no;
-      initializer_tournament_account.opponent_played_on = current_time;
+    let r: u64 = Rent::get()?.minimum_balance(game_state.data_len());
+    if **game_state.lamports.borrow() < r {panic!()}
 
-      initializer_tournament_account.serialize(&mut &mut initializer_tour_acc.data.borrow_mut()[..])?;
+    //every seat's stake sits in the escrow, the game account only holds rent
+    Self::open_escrow(initializer, game_state, escrow, program_id, init.escrow_bump)?;
+    Self::escrow_in(&mut state.escrowed, initializer, escrow, init.lamports)?;
 
-    }
-    if gwins == true{
+    profile.games_hosted += 1;
 
-      let us: usize = initializer_tournament_account.level as usize;
-      let multiply: u64 = t.lvl_get[us] as u64; 
-      let reward:u64 = multiply*t.entrance_fee;
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+    profile.serialize(&mut &mut host_profile.data.borrow_mut()[..])?;
 
-      let multiply: u64 = t.host_get as u64;
-      let host_fee: u64 = (t.entrance_fee/100)*multiply;
+    Ok(())
+  }
+  fn join_ffa(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    join: FfaJoin) -> ProgramResult {
 
-      **initializer_tour_acc.lamports.borrow_mut()-= host_fee;
-      **host.lamports.borrow_mut()+= host_fee;
 
-      **initializer_tour_acc.lamports.borrow_mut()-= reward;
-      **initializer.lamports.borrow_mut()+= reward;
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-      let value: u64 = **initializer_tour_acc.lamports.borrow();
+    let player: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let escrow: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-      **initializer_tour_acc.lamports.borrow_mut()-= value;
-      **opponent_tour_acc.lamports.borrow_mut()+= value;
+    if game_state.owner != program_id{panic!()}
+    if !player.is_signer{panic!()}
 
-      let game: u64 = **game_state.lamports.borrow();
+    let mut state: FfaGameState = FfaGameState::try_from_slice(&game_state.data.borrow())?;
 
-      **game_state.lamports.borrow_mut()-= game;
-      **initializer_tour_acc.lamports.borrow_mut()+= game;
+    let game_state_check: Pubkey = Pubkey::create_with_seed(&Pubkey::new_from_array(state.players[0]), &state.gameseed, program_id).unwrap();
 
-      let str_player_no: String = the_no.to_string();
-      let mut somestr: String = String::from("pppppp");
-      let revstr: String = str_player_no.chars().rev().collect::<String>();
-      let offset: &usize = &revstr.len();
-      somestr.replace_range(..offset, &revstr);
-      let offset2: usize = somestr.len();
-      let mut find_me: String = opponent_tournament_account.player_find;
-      find_me.replace_range(..offset2,&somestr);
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::Open {panic!()}
+    if state.players[..state.joined as usize].contains(&player.key.to_bytes()) {panic!()}
+    if (state.board_roots[0] == [0;32]) != (join.board_root == [0;32]) {panic!()}
+
+    let stake: u64 = state.lamports;
+    Self::check_escrow(state.escrow_bump, state.escrowed, state.lamports*(state.joined as u64), game_state, escrow, program_id)?;
+    Self::escrow_in(&mut state.escrowed, player, escrow, stake)?;
+
+    let seat: usize = state.joined as usize;
+    state.players[seat] = player.key.to_bytes();
+    state.game_hashes[seat] = join.game_hash;
+    state.board_roots[seat] = join.board_root;
+    state.joined += 1;
+
+    if state.joined == state.player_count {
+      state.lastplaytime = Self::current_time(0)?;
+      state.whoseturn = 0;
+      Self::transition(&mut state.status, GameStatus::Active)?;
+    }
 
-      opponent_tournament_account.player_find = find_me;
-      opponent_tournament_account.is_playing = 0;
-      opponent_tournament_account.waiting_opponent_to_join = 0;
-      opponent_tournament_account.level += 1;
-      opponent_tournament_account.playerno_int = the_no;
-      opponent_tournament_account.opponent_played_on = current_time;
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
 
-      opponent_tournament_account.serialize(&mut &mut opponent_tour_acc.data.borrow_mut()[..])?;
+    Ok(())
+  }
+  fn play_ffa(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    shot: FfaShot) -> ProgramResult {
+
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let player: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if game_state.owner != program_id{panic!()}
+    if !player.is_signer{panic!()}
+
+    let mut state: FfaGameState = FfaGameState::try_from_slice(&game_state.data.borrow())?;
 
+    let game_state_check: Pubkey = Pubkey::create_with_seed(&Pubkey::new_from_array(state.players[0]), &state.gameseed, program_id).unwrap();
+
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::Active {panic!()}
+
+    let seat: usize = state.whoseturn as usize;
+    if player.key.to_bytes() != state.players[seat] {panic!()}
+
+    let current_time: u64 = Self::current_time(0)?;
+    let ships_total: u8 = state.fleet.iter().sum();
+
+    //shots fired at this seat since its last turn are reported first
+    let pending: Vec<u8> = state.pending[seat][..state.pending_len[seat] as usize].to_vec();
+    if shot.hits.len() != pending.len() {panic!()}
+
+    for (i, cell) in pending.iter().enumerate() {
+      let hit: u8 = shot.hits[i];
+      if hit > 1 {panic!()}
+      if state.board_roots[seat] != [0;32] {
+        if shot.salts.len() != pending.len() {panic!()}
+        if shot.proofs.len() != pending.len() {panic!()}
+        if !Self::verify_cell_report(&state.board_roots[seat], *cell, hit, &shot.salts[i], &shot.proofs[i]) {
+          return Err(FalseHitReport.into());
+        }
+      }
+      state.hits[seat] += hit;
+      state.board_states[seat][*cell as usize] += hit;
+    }
+    state.pending_len[seat] = 0;
+
+    if state.hits[seat] >= ships_total {
+      state.eliminated |= 1 << seat;
+    }
+    else {
+      let target: usize = shot.target as usize;
+      if target == seat {panic!()}
+      if target >= state.player_count as usize {panic!()}
+      if state.eliminated & (1 << target) != 0 {panic!()}
+      if shot.cell > state.board_width*(state.board_height + 1) {panic!()}
+      if shot.cell < state.board_width + 1 {panic!()}
+
+      let index: usize = shot.cell as usize;
+      if state.board_states[target][index] != 0 {panic!()}
+      state.board_states[target][index] += 1;
+      let queued: usize = state.pending_len[target] as usize;
+      if queued >= FFA_PENDING_CAPACITY {panic!()}
+      state.pending[target][queued] = shot.cell;
+      state.pending_len[target] += 1;
+      state.shots[seat] += 1;
     }
 
+    state.whoseturn = Self::next_ffa_seat(&state);
+    state.lastplaytime = current_time;
+    Self::ffa_check_survivor(&mut state, current_time)?;
+
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+
     Ok(())
   }
-  fn initialize_counter(        
+  fn reveal_ffa(
     accounts: &[AccountInfo],
     program_id: &Pubkey,
-    t_counter: InitTournamentCounter) -> ProgramResult {
+    claim: FfaClaim) -> ProgramResult {
 
-      let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-      let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-      let tournament_counter: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-      let a_k: Pubkey = Pubkey::from_str("4YbLBRXwseG1NuyJbteSD5u81Q2QjFqJBp6JmxwYBKYm").unwrap();
-      if authority.key != &a_k {panic!()}
-      if !authority.is_signer {panic!()}
+    let player: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-      let counter_no_str: String = t_counter.counter_no.to_string();
+    if game_state.owner != program_id{panic!()}
+    if !player.is_signer{panic!()}
 
-      if tournament_counter.owner == &ID && **tournament_counter.lamports.borrow() != 0{
-      let value: u64 = **tournament_counter.lamports.borrow(); 
-      invoke_signed(&system_instruction::transfer(tournament_counter.key, authority.key, value),
-              &[
-              authority.clone(), 
-              tournament_counter.clone(),
-          ],
-          &[&[b"count",counter_no_str.as_ref(), &[t_counter.bump]]])?;
-      }
+    let mut state: FfaGameState = FfaGameState::try_from_slice(&game_state.data.borrow())?;
 
-      invoke_signed(
-          &system_instruction::create_account(
-              &authority.key, 
-              &tournament_counter.key,
-              t_counter.rent,
-              24,
-              &program_id
-          ),
-          &[
-              authority.clone(), 
-              tournament_counter.clone(),
-          ],
-          &[&[b"count",counter_no_str.as_ref(), &[t_counter.bump]]], 
-      )?;
+    let game_state_check: Pubkey = Pubkey::create_with_seed(&Pubkey::new_from_array(state.players[0]), &state.gameseed, program_id).unwrap();
 
-      let counter: TournamentCounter = TournamentCounter{
-        empty:"E".to_string(),
-        counter_no:t_counter.counter_no,
-        player_participating:0,
-        capacity:t_counter.capacity,
-        tournament_id:t_counter.tournament_id,
-      };
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::AwaitingReveal {panic!()}
+    if Self::current_time(0)? >= state.reveal_deadline {panic!()}
 
-      counter.serialize(&mut &mut tournament_counter.data.borrow_mut()[..])?;
+    let seat: usize = match state.players[..state.player_count as usize].iter().position(|p| p == &player.key.to_bytes()) {
+      Some(seat) => seat,
+      None => panic!(),
+    };
+    if state.reveals[seat] != 0 {panic!()}
+
+    let game_arr: Vec<u8> = Self::expand_fleet(&claim.fleet, &state.fleet, state.board_width, state.board_height, state.no_touching)?;
+    let ships_total: u8 = state.fleet.iter().sum();
+
+    let game_hash: keccak::Hash = keccak::hashv(&[claim.seed.to_string().as_bytes(),&game_arr[..],claim.seed.to_string().as_bytes()]);
+    if state.game_hashes[seat] != game_hash.0{panic!()}
+
+    //every reported shot must match the revealed board, only shots still pending may be unreported
+    let mut honest: bool = true;
+    let mut hits: u8 = 0;
+    for cell in game_arr.iter() {
+      let s: u8 = state.board_states[seat][*cell as usize];
+      if s == 2 {hits += 1;}
+      if s == 1 {
+        if !state.pending[seat][..state.pending_len[seat] as usize].contains(cell) {honest = false;}
+        hits += 1;
+      }
+    }
+    let reported: u8 = game_arr.iter().filter(|cell| state.board_states[seat][**cell as usize] == 2).count() as u8;
+    if reported != state.hits[seat] {honest = false;}
 
+    //the survivor's fleet has to actually be afloat
+    if state.eliminated & (1 << seat) == 0 && hits >= ships_total {honest = false;}
 
-      Ok(())
+    state.reveals[seat] = if honest {1} else {2};
+
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+
+    Ok(())
   }
-  fn update_counter_finder(        
+  fn claim_ffa_victory(
     accounts: &[AccountInfo],
-    c_finder: CounterFinder) -> ProgramResult {
-  
-        let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
-  
-        let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-        let finder: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-  
-        let a_k: Pubkey = Pubkey::from_str("4YbLBRXwseG1NuyJbteSD5u81Q2QjFqJBp6JmxwYBKYm").unwrap();
-        if authority.key != &a_k {panic!()}
-        if !authority.is_signer {panic!()}
-  
-      let f: CounterFinder = CounterFinder{
-        finder_no:c_finder.finder_no,
-        counters:c_finder.counters,
-        tournament_id:c_finder.tournament_id,
-      };
+    program_id: &Pubkey,) -> ProgramResult {
 
-      f.serialize(&mut &mut finder.data.borrow_mut()[..])?;
 
-        Ok(())
-  }
-  fn update_finder_finder(        
-        accounts: &[AccountInfo],
-        f_finder: FinderFinder) -> ProgramResult {
-    
-          let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
-  
-          let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-          let finder: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-    
-          let a_k: Pubkey = Pubkey::from_str("4YbLBRXwseG1NuyJbteSD5u81Q2QjFqJBp6JmxwYBKYm").unwrap();
-          if authority.key != &a_k {panic!()}
-          if !authority.is_signer {panic!()}
-    
-        let f: FinderFinder = FinderFinder{
-          finder_no:f_finder.finder_no,
-          counters:f_finder.counters,
-          tournament_id:f_finder.tournament_id,
-        };
-  
-        f.serialize(&mut &mut finder.data.borrow_mut()[..])?;
-    
-          Ok(())
-  }
-  fn delete_account_authority(        
-    accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-      let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let host_profile: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let escrow: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-      let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-      let account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    if game_state.owner != program_id{panic!()}
 
-      let a_k: Pubkey = Pubkey::from_str("4YbLBRXwseG1NuyJbteSD5u81Q2QjFqJBp6JmxwYBKYm").unwrap();
-      if authority.key != &a_k {panic!()}
-      if !authority.is_signer {panic!()}
+    let mut state: FfaGameState = FfaGameState::try_from_slice(&game_state.data.borrow())?;
 
-      let value: u64 = **account.lamports.borrow();
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.players[0]);
+    let host_check: Pubkey = Pubkey::new_from_array(state.host);
+    let game_state_check: Pubkey = Pubkey::create_with_seed(&initializer_check, &state.gameseed, program_id).unwrap();
 
-      **account.lamports.borrow_mut()-= value;
-      **authority.lamports.borrow_mut()+= value;
+    if initializer.key != &initializer_check{panic!()}
+    if host.key != &host_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::AwaitingReveal {panic!()}
+    Self::check_escrow(state.escrow_bump, state.escrowed, state.lamports*(state.player_count as u64), game_state, escrow, program_id)?;
+
+    let seats: usize = state.player_count as usize;
+    let all_revealed: bool = state.reveals[..seats].iter().all(|r| *r != 0);
+    if !all_revealed && Self::current_time(0)? < state.reveal_deadline {panic!()}
+
+    let mut players: Vec<&AccountInfo<'_>> = Vec::new();
+    for key in state.players[..seats].iter() {
+      let player: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      if player.key.to_bytes() != *key {panic!()}
+      players.push(player);
+    }
 
-      Ok(())
+    //the survivor takes the pot on an honest reveal, otherwise it is shared by every seat that
+    //revealed honestly, and if nobody did every stake is returned
+    let survivor: usize = (!state.eliminated).trailing_zeros() as usize;
+    let mut paid: Vec<usize> = Vec::new();
+    if state.reveals[survivor] == 1 {
+      paid.push(survivor);
+      state.winner = survivor as u8 + 1;
+      Self::transition(&mut state.status, GameStatus::Settled)?;
+    }
+    else {
+      paid = (0..seats).filter(|seat| state.reveals[*seat] == 1).collect();
+      if paid.is_empty() {
+        paid = (0..seats).collect();
+        Self::transition(&mut state.status, GameStatus::TimedOut)?;
+      }
+      else {
+        Self::transition(&mut state.status, GameStatus::Settled)?;
+      }
+      state.winner = 0;
+    }
+
+    let pot: u64 = state.lamports*(state.player_count as u64);
+    let host_fee: u64 = Self::host_fee(state.lamports, state.host_fee_bps)*(state.player_count as u64);
+    let share: u64 = (pot - host_fee) / paid.len() as u64;
+
+    Self::escrow_out(&mut state.escrowed, escrow, host, host_fee)?;
+
+    for seat in paid.iter() {
+      Self::escrow_out(&mut state.escrowed, escrow, players[*seat], share)?;
+    }
+
+    //the rounding dust of an uneven share goes back to the initializer
+    let dust: u64 = state.escrowed;
+    Self::escrow_out(&mut state.escrowed, escrow, initializer, dust)?;
+    Self::close_escrow(state.escrowed, escrow, initializer)?;
+
+    Self::credit_host(host_profile, &state.host_profile, host_fee, program_id)?;
+
+    //nothing can follow a free-for-all, the account is closed and the result only survives in the log
+    msg!("ffa game {} winner {} paid {:?}", game_state.key, state.winner, paid);
+    game_state.data.borrow_mut().fill(0);
+
+    let value: u64 = **game_state.lamports.borrow();
+
+    **game_state.lamports.borrow_mut()-= value;
+    **initializer.lamports.borrow_mut()+= value;
+
+    Ok(())
   }
-  fn delete_account(        
-      accounts: &[AccountInfo]) -> ProgramResult {
-  
-        let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+  fn claim_ffa_timeout(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,) -> ProgramResult {
 
-        let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-        let account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
-  
 
-        if !account.is_signer {panic!()}
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
 
-        if !account.is_signer {panic!()}
-  
-        let value: u64 = **account.lamports.borrow();
-  
-        **account.lamports.borrow_mut()-= value;
-        **authority.lamports.borrow_mut()+= value;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
 
-  
-        Ok(())
+    if game_state.owner != program_id{panic!()}
+
+    let mut state: FfaGameState = FfaGameState::try_from_slice(&game_state.data.borrow())?;
+
+    let game_state_check: Pubkey = Pubkey::create_with_seed(&Pubkey::new_from_array(state.players[0]), &state.gameseed, program_id).unwrap();
+
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::Active {panic!()}
+
+    let current_time: u64 = Self::current_time(0)?;
+    if current_time - state.lastplaytime < state.move_timeout {panic!()}
+
+    //the seat on turn is dropped, once one seat is left every board has to be revealed
+    state.eliminated |= 1 << state.whoseturn;
+    state.whoseturn = Self::next_ffa_seat(&state);
+    state.lastplaytime = current_time;
+    Self::ffa_check_survivor(&mut state, current_time)?;
+
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+
+    Ok(())
   }
+  fn abort_ffa(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,) -> ProgramResult {
 
-}
 
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let escrow: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if game_state.owner != program_id{panic!()}
+    if !initializer.is_signer{panic!()}
+
+    let mut state: FfaGameState = FfaGameState::try_from_slice(&game_state.data.borrow())?;
 
+    let game_state_check: Pubkey = Pubkey::create_with_seed(initializer.key, &state.gameseed, program_id).unwrap();
+
+    if game_state.key != &game_state_check{panic!()}
+    if state.status != GameStatus::Open {panic!()}
+    Self::check_escrow(state.escrow_bump, state.escrowed, state.lamports*(state.joined as u64), game_state, escrow, program_id)?;
+
+    Self::transition(&mut state.status, GameStatus::Aborted)?;
+
+    for key in state.players[..state.joined as usize].iter().skip(1) {
+      let player: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      if player.key.to_bytes() != *key {panic!()}
+      Self::escrow_out(&mut state.escrowed, escrow, player, state.lamports)?;
+    }
+    Self::escrow_out(&mut state.escrowed, escrow, initializer, state.lamports)?;
+    Self::close_escrow(state.escrowed, escrow, initializer)?;
+
+    game_state.data.borrow_mut().fill(0);
+
+    let value: u64 = **game_state.lamports.borrow();
+
+    **game_state.lamports.borrow_mut()-= value;
+    **initializer.lamports.borrow_mut()+= value;
+
+    Ok(())
+  }
+  fn init_tournament(        
+    accounts: &[AccountInfo],
+    t: Tournament) -> ProgramResult {
+
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    let authority: Pubkey = Pubkey::from_str("4YbLBRXwseG1NuyJbteSD5u81Q2QjFqJBp6JmxwYBKYm").unwrap();
+
+    if initializer.key != &authority {panic!()}
+    if !initializer.is_signer{panic!()}
+
+    t.serialize(&mut &mut tournament.data.borrow_mut()[..])?;
+    
+
+    Ok(())
+  }
+  fn join_tournament(        
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,) -> ProgramResult {
+
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let participant: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let tournament_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let counter: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
+    let mut t_counter: TournamentCounter = TournamentCounter::try_from_slice(&counter.data.borrow())?;
+
+    let value: u64 = **tournament_account.lamports.borrow();
+
+    if value < t.entrance_fee{panic!()}
+    if tournament.is_writable{panic!()}
+    if tournament.owner != program_id{panic!()}
+    if t.is_init != 1 {panic!()}
+    if tournament_account.owner != program_id{panic!()}
+    if t_counter.player_participating >= t_counter.capacity{panic!()}
+
+
+    t_counter.player_participating += 1;
+
+    let mut str_no: String = String::new();
+    let str_tournament_id: String = t.tournament_id;
+    let str_counter_no: String = t_counter.counter_no.to_string();
+    let str_player_no: String = t_counter.player_participating.to_string();
+    let mut somestr: String = String::from("pppppp");
+    let mut somestr2: String = String::from("cccc");
+    let revstr2: String = str_counter_no.chars().rev().collect::<String>();
+    let revstr: String = str_player_no.chars().rev().collect::<String>();
+    let offset: &usize = &revstr.len();
+    let offset2: &usize = &revstr2.len();
+    somestr.replace_range(..offset, &revstr);
+    somestr2.replace_range(..offset2, &revstr2);
+    str_no += &somestr.chars().rev().collect::<String>();
+    str_no += &somestr2.chars().rev().collect::<String>();
+    str_no += &str_tournament_id;    
+
+
+    let mut opp: u32 = t_counter.player_participating + 1;
+
+    if t_counter.player_participating%2 == 0{
+      opp = t_counter.player_participating - 1;
+    }
+
+    let tournament_account_check: Pubkey = Pubkey::create_with_seed(participant.key, &str_no, program_id).unwrap();
+
+    if tournament_account.key != &tournament_account_check{panic!()}
+
+    let mut referrer_key: [u8;32] = [0;32];
+    let mut referral_share_bps: u16 = 0;
+    if let Ok(referrer) = next_account_info(accounts_iter) {
+      let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      referrer_key = Self::refer(participant, referrer, program_id)?;
+      referral_share_bps = Self::program_config(config_account, program_id)?.referral_share_bps;
+    }
+
+    let t_account: TournamentAccount = TournamentAccount{
+      player_find:str_no,
+      tournamentid:str_tournament_id,
+      player:participant.key.to_bytes(),
+      opponent:opp,
+      level:0,
+      playerno_int:t_counter.player_participating,
+      opponent_played_on:t.starts_at,
+      is_playing:0,
+      waiting_opponent_to_join:0,
+      referrer:referrer_key,
+      referral_share_bps,
+    };
+
+    let full: String = String::from("F");
+
+    if t_counter.player_participating == t_counter.capacity{
+      t_counter.empty = full;
+    }
+
+    t_account.serialize(&mut &mut tournament_account.data.borrow_mut()[..])?;
+    t_counter.serialize(&mut &mut counter.data.borrow_mut()[..])?;
+
+    Ok(())
+  }
+  fn tournament_match_initialize(        
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    init: Init) -> ProgramResult {
+
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let initializer_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
+
+    let o_t_account: TournamentAccount = TournamentAccount::try_from_slice(&opponent_tour_acc.data.borrow())?;
+    let opponent_check: Pubkey = Pubkey::new_from_array(o_t_account.player);
+
+    let mut t_account: TournamentAccount = TournamentAccount::try_from_slice(&initializer_tour_acc.data.borrow())?;
+    let initializer_account_check: Pubkey = Pubkey::new_from_array(t_account.player);
+
+    let mut substract:bool = false;
+    let initializer_no: u32 = o_t_account.playerno_int;
+    let pwr: u32 = o_t_account.level as u32;
+    let mut pwrplus: u32 = o_t_account.level as u32;
+    pwrplus += 1;
+    let pwoftwo:u32 = 2;
+    let dividableby: &u32 = &pwoftwo.pow(pwrplus);
+    if initializer_no%dividableby == 0 {
+      substract = true;
+    }
+    let mut opponent_no: u32 = 0;
+    if substract == false {
+      opponent_no = &initializer_no + &pwoftwo.pow(pwr);
+    }
+    if substract == true {
+      opponent_no = &initializer_no - &pwoftwo.pow(pwr);
+    }
+    let mut game_seed: String = String::new();
+    let opponent_no_str: &String = &opponent_no.to_string();
+    let initializer_no_str: &String = &initializer_no.to_string();
+    let seed: String = String::from("v");
+    if opponent_no > initializer_no{
+      game_seed += initializer_no_str;
+      game_seed += &seed;
+      game_seed += opponent_no_str;
+    }
+    if opponent_no < initializer_no{
+      game_seed += opponent_no_str;
+      game_seed += &seed;
+      game_seed += initializer_no_str;
+    }
+
+    let mut game_id: String = String::new();
+
+    game_id += &t.tournament_id;
+    game_id += &game_seed;
+
+
+    let game_state_check: Pubkey = Pubkey::create_program_address(&[t.tournament_id.to_string().as_ref(),game_seed.as_ref(), &[init.bump]], program_id).unwrap();
+
+    if !initializer.is_signer{panic!()}
+    if t.is_init != 1{panic!()}
+    if t.tournament_id != t_account.tournamentid{panic!()}
+    if t.tournament_id != o_t_account.tournamentid{panic!()}
+    if o_t_account.level == t_account.level && o_t_account.waiting_opponent_to_join != 0 {panic!()}
+    if opponent.key != &opponent_check{panic!()}
+    if initializer.key != &initializer_account_check{panic!()}
+    if game_state.key != &game_state_check{panic!()}
+    if tournament.owner != program_id{panic!()}
+    if opponent_tour_acc.owner != program_id{panic!()}
+    if initializer_tour_acc.owner != program_id{panic!()}
+    if tournament.is_writable {panic!()}
+
+    if init.no_touching > 1 {panic!()}
+    if init.salvo != 0 {panic!()}
+    if !init.initializer_ship_hashes.is_empty() {panic!()}
+    if init.move_timeout != 0 {panic!()}
+    if init.time_unit != 0 {panic!()}
+    if init.time_bank != 0 {panic!()}
+    if init.invited_guest != [0;32] {panic!()}
+    if init.join_key != [0;32] {panic!()}
+    if init.best_of != 0 {panic!()}
+    if init.special_weapons != 0 {panic!()}
+    if init.close_on_settle != 0 {panic!()}
+    if init.expires_at != 0 {panic!()}
+    if init.mint != [0;32] {panic!()}
+    if init.host_fee_bps != 0 {panic!()}
+    if init.log_moves > 1 {panic!()}
+
+    let board_width: u8 = Self::board_dimension(init.board_width);
+    let board_height: u8 = Self::board_dimension(init.board_height);
+
+    let mut state: TGameState = TGameState{
+      game_id,
+      status: GameStatus::Uninitialized,
+      gameseed: String::new(),
+      lamports: 0,
+      initializer: initializer_tour_acc.key.to_bytes(),
+      initializergamehash: init.initializer_game_hash,
+      guest: [0;32],
+      guestgamehash: [0;32],
+      initializer_board_root: init.initializer_board_root,
+      guest_board_root: [0;32],
+      no_touching: init.no_touching,
+      fleet: Self::fleet_definition(init.fleet, board_width, board_height),
+      board_width,
+      board_height,
+      log_moves: init.log_moves,
+      whoseturn: 0,
+      lastplaytime: 0,
+      lastmove: 0,
+      ishots: 0,
+      ihits: 0,
+      gshots: 0,
+      ghits: 0,
+      initializer_board_state: [0;256],
+      guest_board_state: [0;256],
+    };
+    Self::transition(&mut state.status, GameStatus::Open)?;
+
+    //the game id and fleet are never resized, so the account is sized to the state it starts with
+    let size: usize = state.try_to_vec()?.len();
+
+    invoke_signed(
+      &system_instruction::create_account(
+          initializer.key,
+          game_state.key,
+          Rent::get()?.minimum_balance(size),
+          size as u64,
+          program_id
+      ),
+      &[
+        initializer.clone(),
+        game_state.clone(),
+      ],
+      &[&[t.tournament_id.to_string().as_ref(),game_seed.as_ref(), &[init.bump]]],
+    )?;
+
+    if game_state.owner != program_id{panic!()}
+
+    if init.log_moves == 1 {
+      let move_log: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      Self::create_move_log(initializer, game_state, move_log, program_id, init.log_bump)?;
+    }
+
+
+    t_account.opponent = opponent_no;
+    t_account.waiting_opponent_to_join = 1;
+
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+    t_account.serialize(&mut &mut initializer_tour_acc.data.borrow_mut()[..])?;
+
+    Ok(())
+  }
+  fn tournament_match_accept(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    join: Join) -> ProgramResult {
+
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let initializer_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
+
+    let mut o_t_account: TournamentAccount = TournamentAccount::try_from_slice(&opponent_tour_acc.data.borrow())?;
+    let opponent_check: Pubkey = Pubkey::new_from_array(o_t_account.player);
+
+    let mut t_account: TournamentAccount = TournamentAccount::try_from_slice(&initializer_tour_acc.data.borrow())?;
+    let initializer_account_check: Pubkey = Pubkey::new_from_array(t_account.player);
+
+        if opponent.key != &opponent_check{panic!()}
+    if initializer.key != &initializer_account_check{panic!()}
+
+    let mut state: TGameState = TGameState::try_from_slice(&game_state.data.borrow())?;
+
+
+    if !opponent.is_signer{panic!()}
+    if t.is_init != 1{panic!()}
+    if t.tournament_id != t_account.tournamentid{panic!()}
+    if t.tournament_id != o_t_account.tournamentid{panic!()}
+    if o_t_account.level == t_account.level {panic!()}
+    if o_t_account.playerno_int == t_account.opponent {panic!()}
+
+    if state.initializer != t_account.player {panic!()}
+    if tournament.owner != program_id{panic!()}
+    if opponent_tour_acc.owner != program_id{panic!()}
+    if initializer_tour_acc.owner != program_id{panic!()}
+    if game_state.owner != program_id{panic!()}
+    if state.status != GameStatus::Open {panic!()}
+    if tournament.is_writable {panic!()}
+
+    if state.initializer_board_root == [0;32] && join.guest_board_root != [0;32] {panic!()}
+    if state.initializer_board_root != [0;32] && join.guest_board_root == [0;32] {panic!()}
+
+    state.guest = opponent.key.to_bytes();
+    state.guestgamehash = join.guest_game_hash;
+    state.guest_board_root = join.guest_board_root;
+    Self::transition(&mut state.status, GameStatus::Active)?;
+    state.whoseturn = 1;
+
+    let clock: Clock= Clock::get()?;
+    let current_time: u64 = clock.unix_timestamp as u64;
+
+    o_t_account.is_playing = 1;
+    t_account.opponent_played_on = current_time;
+    t_account.is_playing = 1;
+    t_account.waiting_opponent_to_join = 0;
+
+    o_t_account.serialize(&mut &mut opponent_tour_acc.data.borrow_mut()[..])?;
+    t_account.serialize(&mut &mut initializer_tour_acc.data.borrow_mut()[..])?;
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+
+    Ok(())
+  }
+  fn tournament_play(        
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    m: Move,) -> ProgramResult {
+    
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let initializer_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    let mut state: TGameState = TGameState::try_from_slice(&game_state.data.borrow())?;
+
+    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
+
+    let opponent_tournament_account: TournamentAccount = TournamentAccount::try_from_slice(&opponent_tour_acc.data.borrow())?;
+
+    let opponent_check: Pubkey = Pubkey::new_from_array(opponent_tournament_account.player);
+
+    let initializer_tournament_account: TournamentAccount = TournamentAccount::try_from_slice(&initializer_tour_acc.data.borrow())?;
+
+    let initializer_account_check: Pubkey = Pubkey::new_from_array(initializer_tournament_account.player);
+
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+
+    if t.is_init != 1{panic!()}
+    if t.tournament_id != initializer_tournament_account.tournamentid{panic!()}
+    if t.tournament_id != opponent_tournament_account.tournamentid{panic!()}
+    if state.initializer != initializer_tournament_account.player{panic!()}
+    if state.guest != opponent_tournament_account.player{panic!()}
+    if opponent_tournament_account.level == initializer_tournament_account.level {panic!()}
+    if opponent_tournament_account.playerno_int == initializer_tournament_account.opponent {panic!()}
+    if opponent_tournament_account.opponent == initializer_tournament_account.playerno_int {panic!()}
+    if opponent.key != &opponent_check{panic!()}
+    if initializer.key != &initializer_account_check{panic!()}
+    if initializer.key != &initializer_check{panic!()}
+    if tournament.owner != program_id{panic!()}
+    if opponent_tour_acc.owner != program_id{panic!()}
+    if initializer_tour_acc.owner != program_id{panic!()}
+    if game_state.owner != program_id{panic!()}
+    if tournament.is_writable {panic!()}
+    if opponent_tournament_account.is_playing != 1{panic!()}
+    if initializer_tournament_account.is_playing != 1{panic!()}
+
+    
+    if state.status != GameStatus::Active {panic!()}
+    if m.mymove > state.board_width*(state.board_height + 1) {panic!()}
+    if m.mymove < state.board_width + 1 {panic!()}
+    if state.lastmove == 0 && m.opponents_last_move_is_hit != 0 {panic!()}
+    if m.opponents_last_move_is_hit > 1 {panic!()}
+
+    let clock: Clock= Clock::get()?;
+    let current_time: u64 = clock.unix_timestamp as u64;
+
+    let mut turn:u8 = 0;
+    let hit:u8 = m.opponents_last_move_is_hit;
+    let index: usize = m.mymove as usize;
+    let index2: usize = state.lastmove as usize;
+
+
+    if state.whoseturn == 1{ // 1 initializer
+      if !initializer.is_signer{panic!()};
+      if state.initializer_board_root != [0;32] && state.lastmove != 0
+        && !Self::verify_cell_report(&state.initializer_board_root, state.lastmove, hit, &m.salt, &m.proof) {
+        return Err(FalseHitReport.into());
+      }
+      turn = 2;
+      state.ishots += 1;
+      state.ghits += hit;
+      if state.guest_board_state[index] != 0{panic!()};
+      state.guest_board_state[index] += 1;
+      state.initializer_board_state[index2] += hit;
+    }//hit 2
+     //miss 1
+     //unchecked 0
+
+    if state.whoseturn == 2{ // 2 guest
+      if !opponent.is_signer{panic!()};
+      if state.guest_board_root != [0;32] && state.lastmove != 0
+        && !Self::verify_cell_report(&state.guest_board_root, state.lastmove, hit, &m.salt, &m.proof) {
+        return Err(FalseHitReport.into());
+      }
+      turn = 1;
+      state.gshots += 1;
+      state.ihits += hit;
+      if state.initializer_board_state[index] != 0{panic!()};
+      state.initializer_board_state[index] += 1;
+      state.guest_board_state[index2] += hit;
+    }
+
+    if state.log_moves == 1 {
+      let move_log: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let mover: &AccountInfo<'_> = if state.whoseturn == 1 {initializer} else {opponent};
+      let mut records: Vec<MoveRecord> = Vec::new();
+      if state.lastmove != 0 {
+        records.push(MoveRecord{shooter: turn, weapon: WEAPON_SHOT, cell: state.lastmove, result: 1 + hit, timestamp: current_time});
+      }
+      records.push(MoveRecord{shooter: state.whoseturn, weapon: WEAPON_SHOT, cell: m.mymove, result: 0, timestamp: current_time});
+      Self::append_move_log(mover, game_state, move_log, program_id, records)?;
+    }
+
+    state.lastplaytime = current_time;
+    state.whoseturn  = turn;
+    state.lastmove = m.mymove;
+
+
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+
+    Ok(())
+  }
+  fn tournament_finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    report: FinalReport,) -> ProgramResult {
+
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    if game_state.owner != program_id{panic!()}
+
+    let mut state: TGameState = TGameState::try_from_slice(&game_state.data.borrow())?;
+
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+    let opponent_check: Pubkey = Pubkey::new_from_array(state.guest);
+
+    if initializer.key != &initializer_check{panic!()}
+    if opponent.key != &opponent_check{panic!()}
+    if state.status != GameStatus::Active {panic!()}
+
+    //the defender answers the shot fired at it without firing back, so the score is exact before a claim
+    let defender: u8 = state.whoseturn;
+    if defender == 1 && !initializer.is_signer {panic!()}
+    if defender == 2 && !opponent.is_signer {panic!()}
+    if defender != 1 && defender != 2 {panic!()}
+    if state.lastmove == 0 {panic!()}
+    if report.hits.len() != 1 {panic!()}
+
+    let hit: u8 = report.hits[0];
+    let cell: u8 = state.lastmove;
+    if hit > 1 {panic!()}
+
+    let root: [u8;32] = if defender == 1 {state.initializer_board_root} else {state.guest_board_root};
+    if root != [0;32] {
+      if report.salts.len() != 1 {panic!()}
+      if report.proofs.len() != 1 {panic!()}
+      if !Self::verify_cell_report(&root, cell, hit, &report.salts[0], &report.proofs[0]) {
+        return Err(FalseHitReport.into());
+      }
+    }
+
+    if defender == 1 {
+      state.initializer_board_state[cell as usize] += hit;
+      state.ghits += hit;
+    }else{
+      state.guest_board_state[cell as usize] += hit;
+      state.ihits += hit;
+    }
+
+    if state.log_moves == 1 {
+      let move_log: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let payer: &AccountInfo<'_> = if defender == 1 {initializer} else {opponent};
+      let current_time: u64 = Clock::get()?.unix_timestamp as u64;
+      let records: Vec<MoveRecord> = vec![MoveRecord{shooter: 3 - defender, weapon: WEAPON_SHOT, cell, result: 1 + hit, timestamp: current_time}];
+      Self::append_move_log(payer, game_state, move_log, program_id, records)?;
+    }
+
+    state.lastmove = 0;
+
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+
+    Ok(())
+  }
+  fn tournament_claim_victory_by_score(        
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    claim: ClaimVictory,) -> ProgramResult {
+    
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let initializer_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    let mut state: TGameState = TGameState::try_from_slice(&game_state.data.borrow())?;
+
+    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
+
+    let mut o_t_account: TournamentAccount = TournamentAccount::try_from_slice(&opponent_tour_acc.data.borrow())?;
+
+    let opponent_check: Pubkey = Pubkey::new_from_array(o_t_account.player);
+
+    let mut t_account: TournamentAccount = TournamentAccount::try_from_slice(&initializer_tour_acc.data.borrow())?;
+
+    let initializer_account_check: Pubkey = Pubkey::new_from_array(t_account.player);
+
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+
+    if t.is_init != 1{panic!()}
+    if t.tournament_id != t_account.tournamentid{panic!()}
+    if t.tournament_id != o_t_account.tournamentid{panic!()}
+    if state.initializer != t_account.player{panic!()}
+    if state.guest != o_t_account.player{panic!()}
+    if o_t_account.level == t_account.level {panic!()}
+    if o_t_account.playerno_int == t_account.opponent {panic!()}
+    if o_t_account.opponent == t_account.playerno_int {panic!()}
+    if opponent.key != &opponent_check{panic!()}
+    if initializer.key != &initializer_account_check{panic!()}
+    if initializer.key != &initializer_check{panic!()}
+    if tournament.owner != program_id{panic!()}
+    if opponent_tour_acc.owner != program_id{panic!()}
+    if initializer_tour_acc.owner != program_id{panic!()}
+    if game_state.owner != program_id{panic!()}
+    if tournament.is_writable {panic!()}
+    if o_t_account.is_playing != 1{panic!()}
+    if t_account.is_playing != 1{panic!()}
+
+    
+    if state.status != GameStatus::Active {panic!()}
+
+
+    let clock: Clock= Clock::get()?;
+    let current_time: u64 = clock.unix_timestamp as u64;
+
+    let game_arr: Vec<u8> = Self::expand_fleet(&claim.fleet, &state.fleet, state.board_width, state.board_height, state.no_touching)?;
+    let ships_total: u8 = state.fleet.iter().sum();
+
+    let game_hash: keccak::Hash = keccak::hashv(&[claim.seed.to_string().as_bytes(),&game_arr[..],claim.seed.to_string().as_bytes()]);
+
+
+    if claim.claims == 1 {
+      if state.initializergamehash != game_hash.0{panic!()}
+    }
+    else {
+      if state.guestgamehash != game_hash.0{panic!()}
+    }
+
+    //the last shot fired at the claimer was never reported, the revealed board answers it
+    let mut pending: Vec<u8> = Vec::new();
+    if state.lastmove != 0 && state.whoseturn == claim.claims {pending = vec![state.lastmove];}
+
+    if claim.claims == 1 {
+      let (consistent, pending_hits): (bool, u8) = Self::board_matches(&game_arr, &state.initializer_board_state, &pending);
+      state.ghits += pending_hits;
+      if !consistent {
+        state.ihits = 0;
+        state.ghits = ships_total;
+      }
+    }else{
+      let (consistent, pending_hits): (bool, u8) = Self::board_matches(&game_arr, &state.guest_board_state, &pending);
+      state.ihits += pending_hits;
+      if !consistent {
+        state.ihits = ships_total;
+        state.ghits = 0;
+      }
+    }
+
+
+    let mut draw:bool=false;
+    let mut iwins:bool=false;
+    let mut gwins:bool=false;
+
+    let mut the_no = 0;
+    if t_account.playerno_int>o_t_account.playerno_int{
+      the_no = t_account.playerno_int;
+    }
+    if t_account.playerno_int<o_t_account.playerno_int{
+      the_no = o_t_account.playerno_int;
+    }
+
+    if state.ihits >= ships_total{iwins = true;}
+    if state.ghits >= ships_total{gwins = true;}
+    if gwins && iwins{draw = true;iwins = false;gwins = false;}
+
+    if iwins || gwins || draw {
+      Self::transition(&mut state.status, GameStatus::Settled)?;
+      state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+    }
+
+    if iwins == true{
+
+      let us: usize = o_t_account.level as usize;
+      let multiply: u64 = t.lvl_get[us] as u64; 
+      let reward:u64 = multiply*t.entrance_fee;
+
+      let multiply: u64 = t.host_get as u64;
+      let host_fee: u64 = (t.entrance_fee/100)*multiply;
+
+      let mut referred: u64 = 0;
+      if o_t_account.referrer != [0;32] {
+        let referrer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        referred = Self::pay_referral(opponent_tour_acc, referrer, &o_t_account.referrer, o_t_account.referral_share_bps, host_fee, program_id)?;
+      }
+
+      **opponent_tour_acc.lamports.borrow_mut()-= host_fee - referred;
+      **host.lamports.borrow_mut()+= host_fee - referred;
+
+      **opponent_tour_acc.lamports.borrow_mut()-= reward;
+      **opponent.lamports.borrow_mut()+= reward;
+
+      let value: u64 = **opponent_tour_acc.lamports.borrow();
+
+      **opponent_tour_acc.lamports.borrow_mut()-= value;
+      **initializer_tour_acc.lamports.borrow_mut()+= value;
+
+      let game: u64 = **game_state.lamports.borrow();
+
+      **game_state.lamports.borrow_mut()-= game;
+      **initializer_tour_acc.lamports.borrow_mut()+= game;
+
+      let str_player_no: String = the_no.to_string();
+      let mut somestr: String = String::from("pppppp");
+      let revstr: String = str_player_no.chars().rev().collect::<String>();
+      let offset: &usize = &revstr.len();
+      somestr.replace_range(..offset, &revstr);
+      let offset2: usize = somestr.len();
+      let mut find_me: String = t_account.player_find;
+      find_me.replace_range(..offset2,&somestr);
+
+      t_account.player_find = find_me;
+      t_account.is_playing = 0;
+      t_account.waiting_opponent_to_join = 0;
+      t_account.level += 1;
+      t_account.playerno_int = the_no;
+      t_account.opponent_played_on = current_time;
+
+      t_account.serialize(&mut &mut initializer_tour_acc.data.borrow_mut()[..])?;
+
+    }
+    if gwins == true{
+      let us: usize = t_account.level as usize;
+      let multiply: u64 = t.lvl_get[us] as u64; 
+      let reward:u64 = multiply*t.entrance_fee;
+
+      let multiply: u64 = t.host_get as u64;
+      let host_fee: u64 = (t.entrance_fee/100)*multiply;
+
+      let mut referred: u64 = 0;
+      if t_account.referrer != [0;32] {
+        let referrer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        referred = Self::pay_referral(initializer_tour_acc, referrer, &t_account.referrer, t_account.referral_share_bps, host_fee, program_id)?;
+      }
+
+      **initializer_tour_acc.lamports.borrow_mut()-= host_fee - referred;
+      **host.lamports.borrow_mut()+= host_fee - referred;
+
+      **initializer_tour_acc.lamports.borrow_mut()-= reward;
+      **initializer.lamports.borrow_mut()+= reward;
+
+      let value: u64 = **initializer_tour_acc.lamports.borrow();
+
+      **initializer_tour_acc.lamports.borrow_mut()-= value;
+      **opponent_tour_acc.lamports.borrow_mut()+= value;
+
+      let game: u64 = **game_state.lamports.borrow();
+
+      **game_state.lamports.borrow_mut()-= game;
+      **initializer_tour_acc.lamports.borrow_mut()+= game;
+
+      let str_player_no: String = the_no.to_string();
+      let mut somestr: String = String::from("pppppp");
+      let revstr: String = str_player_no.chars().rev().collect::<String>();
+      let offset: &usize = &revstr.len();
+      somestr.replace_range(..offset, &revstr);
+      let offset2: usize = somestr.len();
+      let mut find_me: String = o_t_account.player_find;
+      find_me.replace_range(..offset2,&somestr);
+
+      o_t_account.player_find = find_me;
+      o_t_account.is_playing = 0;
+      o_t_account.waiting_opponent_to_join = 0;
+      o_t_account.level += 1;
+      o_t_account.playerno_int = the_no;
+      o_t_account.opponent_played_on = current_time;
+
+      o_t_account.serialize(&mut &mut opponent_tour_acc.data.borrow_mut()[..])?;
+
+    }
+    if draw == true{
+
+      o_t_account.is_playing = 0;
+      o_t_account.waiting_opponent_to_join = 0;
+      o_t_account.opponent_played_on = current_time;
+
+      t_account.is_playing = 0;
+      t_account.waiting_opponent_to_join = 0;
+      t_account.opponent_played_on = current_time;
+
+      let value: u64 = **game_state.lamports.borrow();
+
+      **game_state.lamports.borrow_mut()-= value;
+      **initializer.lamports.borrow_mut()+= value;
+
+      t_account.serialize(&mut &mut initializer_tour_acc.data.borrow_mut()[..])?;
+      o_t_account.serialize(&mut &mut opponent_tour_acc.data.borrow_mut()[..])?;
+
+    }
+    
+    Ok(())
+  }
+  fn eliminate_player_who_hasnt_initialized_his_game(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,) -> ProgramResult {
+
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let eliminate: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let eliminate_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
+
+    let el_t_account: TournamentAccount = TournamentAccount::try_from_slice(&eliminate_tour_acc.data.borrow())?;
+
+    let el_check: Pubkey = Pubkey::new_from_array(el_t_account.player);
+
+    let mut op_t_account: TournamentAccount = TournamentAccount::try_from_slice(&opponent_tour_acc.data.borrow())?;
+
+    let opponent_check: Pubkey = Pubkey::new_from_array(op_t_account.player);
+
+
+    if t.is_init != 1{panic!()}
+    if t.tournament_id != el_t_account.tournamentid{panic!()}
+    if t.tournament_id != op_t_account.tournamentid{panic!()}
+    if eliminate.key != &el_check{panic!()}
+    if opponent.key != &opponent_check{panic!()}
+    if tournament.owner != program_id{panic!()}
+    if el_t_account.waiting_opponent_to_join != 0 {panic!()}
+    if eliminate_tour_acc.owner != program_id{panic!()}
+    if tournament.is_writable {panic!()}
+    if el_t_account.is_playing != 0  {panic!()}
+    if el_t_account.opponent != op_t_account.playerno_int{panic!()}
+    if el_t_account.playerno_int != op_t_account.opponent{panic!()}
+    if el_t_account.level != op_t_account.level{panic!()}
+
+    let clock: Clock= Clock::get()?;
+    let current_time: u64 = clock.unix_timestamp as u64;
+    let time_passed: u64 = current_time - el_t_account.opponent_played_on;
+
+    if time_passed < t.time_is_up {panic!()}
+
+    let mut the_no: u32 = 0;
+    if el_t_account.playerno_int>op_t_account.playerno_int{
+      the_no = el_t_account.playerno_int;
+    }
+    if el_t_account.playerno_int<op_t_account.playerno_int{
+      the_no = op_t_account.playerno_int;
+    }
+
+    let us: usize = el_t_account.level as usize;
+    let multiply: u64 = t.lvl_get[us] as u64;
+    let reward:u64 = multiply*t.entrance_fee;
+
+    let multiply: u64 = t.host_get as u64;
+    let host_fee: u64 = (t.entrance_fee/100)*multiply;
+
+    let mut referred: u64 = 0;
+    if el_t_account.referrer != [0;32] {
+      let referrer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      referred = Self::pay_referral(eliminate_tour_acc, referrer, &el_t_account.referrer, el_t_account.referral_share_bps, host_fee, program_id)?;
+    }
+
+    **eliminate_tour_acc.lamports.borrow_mut()-= host_fee - referred;
+    **host.lamports.borrow_mut()+= host_fee - referred;
+
+    **eliminate_tour_acc.lamports.borrow_mut()-= reward;
+    **eliminate.lamports.borrow_mut()+= reward;
+
+    let value: u64 = **eliminate_tour_acc.lamports.borrow();
+
+    **eliminate_tour_acc.lamports.borrow_mut()-= value;
+    **opponent_tour_acc.lamports.borrow_mut()+= value;
+
+    let str_player_no: String = the_no.to_string();
+    let mut somestr: String = String::from("pppppp");
+    let revstr: String = str_player_no.chars().rev().collect::<String>();
+    let offset: &usize = &revstr.len();
+    somestr.replace_range(..offset, &revstr);
+    let offset2: usize = somestr.len();
+    let mut find_me: String = op_t_account.player_find;
+    find_me.replace_range(..offset2,&somestr);
+
+    op_t_account.player_find = find_me;
+    op_t_account.is_playing = 0;
+    op_t_account.waiting_opponent_to_join = 0;
+    op_t_account.level += 1;
+    op_t_account.playerno_int = the_no;
+
+    op_t_account.serialize(&mut &mut opponent_tour_acc.data.borrow_mut()[..])?;
+
+
+    Ok(())
+  }
+  fn eliminate_player_who_hasnt_made_his_move(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,) -> ProgramResult {
+
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+    let initializer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let initializer_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let opponent_tour_acc: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let game_state: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let tournament: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    let host: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    let mut state: TGameState = TGameState::try_from_slice(&game_state.data.borrow())?;
+
+    let t: Tournament = Tournament::try_from_slice(&tournament.data.borrow())?;
+
+    let mut opponent_tournament_account: TournamentAccount = TournamentAccount::try_from_slice(&opponent_tour_acc.data.borrow())?;
+
+    let opponent_check: Pubkey = Pubkey::new_from_array(opponent_tournament_account.player);
+
+    let mut initializer_tournament_account: TournamentAccount = TournamentAccount::try_from_slice(&initializer_tour_acc.data.borrow())?;
+
+    let initializer_account_check: Pubkey = Pubkey::new_from_array(initializer_tournament_account.player);
+
+    let initializer_check: Pubkey = Pubkey::new_from_array(state.initializer);
+
+    if t.is_init != 1{panic!()}
+    if t.tournament_id != initializer_tournament_account.tournamentid{panic!()}
+    if t.tournament_id != opponent_tournament_account.tournamentid{panic!()}
+    if state.initializer != initializer_tournament_account.player{panic!()}
+    if state.guest != opponent_tournament_account.player{panic!()}
+    if opponent_tournament_account.level == initializer_tournament_account.level {panic!()}
+    if opponent_tournament_account.opponent == initializer_tournament_account.playerno_int {panic!()}
+    if opponent_tournament_account.playerno_int == initializer_tournament_account.opponent {panic!()}
+    if opponent.key != &opponent_check{panic!()}
+    if initializer.key != &initializer_account_check{panic!()}
+    if initializer.key != &initializer_check{panic!()}
+    if tournament.owner != program_id{panic!()}
+    if opponent_tour_acc.owner != program_id{panic!()}
+    if initializer_tour_acc.owner != program_id{panic!()}
+    if game_state.owner != program_id{panic!()}
+    if tournament.is_writable {panic!()}
+    if opponent_tournament_account.is_playing != 1{panic!()}
+    if initializer_tournament_account.is_playing != 1{panic!()}
+    if state.status != GameStatus::Active {panic!()}
+
+
+    let mut the_no: u32 = 0;
+    if initializer_tournament_account.playerno_int>opponent_tournament_account.playerno_int{
+      the_no = initializer_tournament_account.playerno_int;
+    }
+    if initializer_tournament_account.playerno_int<opponent_tournament_account.playerno_int{
+      the_no = opponent_tournament_account.playerno_int;
+    }
+
+    let mut iwins:bool=false;
+    let mut gwins:bool=false;
+
+    let clock: Clock= Clock::get()?;
+    let current_time: u64 = clock.unix_timestamp as u64;
+
+
+    if state.whoseturn == 1{
+      let time_passed: u64 = current_time - state.lastplaytime;
+      if time_passed > t.time_is_up {gwins=true;}
+      if time_passed < t.time_is_up {panic!()}
+    }
+    if state.whoseturn == 2{
+      let time_passed: u64 = current_time - state.lastplaytime;
+      if time_passed > t.time_is_up {iwins=true;}
+      if time_passed < t.time_is_up {panic!()}
+    }
+
+    if !iwins && !gwins{panic!()}
+    if !iwins && gwins{panic!()}
+
+    Self::transition(&mut state.status, GameStatus::TimedOut)?;
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+
+    if iwins == true{
+
+      let us: usize = opponent_tournament_account.level as usize;
+      let multiply: u64 = t.lvl_get[us] as u64; 
+      let reward:u64 = multiply*t.entrance_fee;
+
+      let multiply: u64 = t.host_get as u64;
+      let host_fee: u64 = (t.entrance_fee/100)*multiply;
+
+      let mut referred: u64 = 0;
+      if opponent_tournament_account.referrer != [0;32] {
+        let referrer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        referred = Self::pay_referral(opponent_tour_acc, referrer, &opponent_tournament_account.referrer, opponent_tournament_account.referral_share_bps, host_fee, program_id)?;
+      }
+
+      **opponent_tour_acc.lamports.borrow_mut()-= host_fee - referred;
+      **host.lamports.borrow_mut()+= host_fee - referred;
+
+      **opponent_tour_acc.lamports.borrow_mut()-= reward;
+      **opponent.lamports.borrow_mut()+= reward;
+
+      let value: u64 = **opponent_tour_acc.lamports.borrow();
+
+      **opponent_tour_acc.lamports.borrow_mut()-= value;
+      **initializer_tour_acc.lamports.borrow_mut()+= value;
+
+      let game: u64 = **game_state.lamports.borrow();
+
+      **game_state.lamports.borrow_mut()-= game;
+      **initializer_tour_acc.lamports.borrow_mut()+= game;
+
+      let str_player_no: String = the_no.to_string();
+      let mut somestr: String = String::from("pppppp");
+      let revstr: String = str_player_no.chars().rev().collect::<String>();
+      let offset: &usize = &revstr.len();
+      somestr.replace_range(..offset, &revstr);
+      let offset2: usize = somestr.len();
+      let mut find_me: String = initializer_tournament_account.player_find;
+      find_me.replace_range(..offset2,&somestr);
+
+      initializer_tournament_account.player_find = find_me;
+      initializer_tournament_account.is_playing = 0;
+      initializer_tournament_account.waiting_opponent_to_join = 0;
+      initializer_tournament_account.level += 1;
+      initializer_tournament_account.playerno_int = the_no;
+      initializer_tournament_account.opponent_played_on = current_time;
+
+      initializer_tournament_account.serialize(&mut &mut initializer_tour_acc.data.borrow_mut()[..])?;
+
+    }
+    if gwins == true{
+
+      let us: usize = initializer_tournament_account.level as usize;
+      let multiply: u64 = t.lvl_get[us] as u64; 
+      let reward:u64 = multiply*t.entrance_fee;
+
+      let multiply: u64 = t.host_get as u64;
+      let host_fee: u64 = (t.entrance_fee/100)*multiply;
+
+      let mut referred: u64 = 0;
+      if initializer_tournament_account.referrer != [0;32] {
+        let referrer: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        referred = Self::pay_referral(initializer_tour_acc, referrer, &initializer_tournament_account.referrer, initializer_tournament_account.referral_share_bps, host_fee, program_id)?;
+      }
+
+      **initializer_tour_acc.lamports.borrow_mut()-= host_fee - referred;
+      **host.lamports.borrow_mut()+= host_fee - referred;
+
+      **initializer_tour_acc.lamports.borrow_mut()-= reward;
+      **initializer.lamports.borrow_mut()+= reward;
+
+      let value: u64 = **initializer_tour_acc.lamports.borrow();
+
+      **initializer_tour_acc.lamports.borrow_mut()-= value;
+      **opponent_tour_acc.lamports.borrow_mut()+= value;
+
+      let game: u64 = **game_state.lamports.borrow();
+
+      **game_state.lamports.borrow_mut()-= game;
+      **initializer_tour_acc.lamports.borrow_mut()+= game;
+
+      let str_player_no: String = the_no.to_string();
+      let mut somestr: String = String::from("pppppp");
+      let revstr: String = str_player_no.chars().rev().collect::<String>();
+      let offset: &usize = &revstr.len();
+      somestr.replace_range(..offset, &revstr);
+      let offset2: usize = somestr.len();
+      let mut find_me: String = opponent_tournament_account.player_find;
+      find_me.replace_range(..offset2,&somestr);
+
+      opponent_tournament_account.player_find = find_me;
+      opponent_tournament_account.is_playing = 0;
+      opponent_tournament_account.waiting_opponent_to_join = 0;
+      opponent_tournament_account.level += 1;
+      opponent_tournament_account.playerno_int = the_no;
+      opponent_tournament_account.opponent_played_on = current_time;
+
+      opponent_tournament_account.serialize(&mut &mut opponent_tour_acc.data.borrow_mut()[..])?;
+
+    }
+
+    Ok(())
+  }
+  fn update_config(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    config: ProgramConfig) -> ProgramResult {
+
+      let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+      let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+      let a_k: Pubkey = Pubkey::from_str("4YbLBRXwseG1NuyJbteSD5u81Q2QjFqJBp6JmxwYBKYm").unwrap();
+      if authority.key != &a_k {panic!()}
+      if !authority.is_signer {panic!()}
+
+      if config.min_timeout_seconds > config.max_timeout_seconds {panic!()}
+      if config.min_timeout_slots > config.max_timeout_slots {panic!()}
+      if config.min_host_fee_bps > config.max_host_fee_bps {panic!()}
+      if config.max_host_fee_bps > 10_000 {panic!()}
+      if config.referral_share_bps > 10_000 {panic!()}
+
+      let config_check: Pubkey = Pubkey::create_program_address(&[b"config", &[config.bump]], program_id).unwrap();
+      if config_account.key != &config_check {panic!()}
+
+      let size: usize = config.try_to_vec()?.len();
+      let rent: u64 = Rent::get()?.minimum_balance(size);
+
+      if config_account.owner != program_id {
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                config_account.key,
+                rent,
+                size as u64,
+                program_id
+            ),
+            &[
+                authority.clone(),
+                config_account.clone(),
+            ],
+            &[&[b"config", &[config.bump]]],
+        )?;
+      }
+      else if config_account.data_len() != size {
+        let missing: u64 = rent.saturating_sub(**config_account.lamports.borrow());
+        if missing > 0 {
+          invoke(&system_instruction::transfer(authority.key, config_account.key, missing),
+              &[
+                authority.clone(),
+                config_account.clone(),
+              ])?;
+        }
+        config_account.realloc(size, false)?;
+      }
+
+      config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+      Ok(())
+  }
+  fn register_host(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    host: RegisterHost) -> ProgramResult {
+
+      let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+      let operator: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let host_profile: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let config_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+      let config: ProgramConfig = Self::program_config(config_account, program_id)?;
+
+      if !operator.is_signer {panic!()}
+      if host.enabled > 1 {panic!()}
+      if host.fee_bps < config.min_host_fee_bps || host.fee_bps > config.max_host_fee_bps {panic!()}
+
+      let profile_check: Pubkey = Pubkey::create_program_address(&[b"host", operator.key.as_ref(), &[host.bump]], program_id).unwrap();
+      if host_profile.key != &profile_check {panic!()}
+
+      let mut profile: HostProfile = HostProfile{
+        operator: operator.key.to_bytes(),
+        payout: host.payout,
+        fee_bps: host.fee_bps,
+        enabled: host.enabled,
+        games_hosted: 0,
+        fees_earned: 0,
+        bump: host.bump,
+      };
+
+      if host_profile.owner != program_id {
+        let size: usize = profile.try_to_vec()?.len();
+
+        invoke_signed(
+            &system_instruction::create_account(
+                operator.key,
+                host_profile.key,
+                Rent::get()?.minimum_balance(size),
+                size as u64,
+                program_id
+            ),
+            &[
+              operator.clone(),
+              host_profile.clone(),
+            ],
+            &[&[b"host", operator.key.as_ref(), &[host.bump]]],
+        )?;
+      }
+      else {
+        let current: HostProfile = HostProfile::try_from_slice(&host_profile.data.borrow())?;
+        if current.operator != operator.key.to_bytes() {panic!()}
+        profile.games_hosted = current.games_hosted;
+        profile.fees_earned = current.fees_earned;
+      }
+
+      profile.serialize(&mut &mut host_profile.data.borrow_mut()[..])?;
+
+      Ok(())
+  }
+  fn register_referrer(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    referrer: RegisterReferrer) -> ProgramResult {
+
+      let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+      let partner: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let referrer_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+      if !partner.is_signer {panic!()}
+      if referrer_account.owner == program_id {panic!()}
+
+      let referrer_check: Pubkey = Pubkey::create_program_address(&[b"referrer", partner.key.as_ref(), &[referrer.bump]], program_id).unwrap();
+      if referrer_account.key != &referrer_check {panic!()}
+
+      let profile: ReferrerProfile = ReferrerProfile{
+        referrer: partner.key.to_bytes(),
+        games_referred: 0,
+        earned: 0,
+        bump: referrer.bump,
+      };
+
+      let size: usize = profile.try_to_vec()?.len();
+
+      invoke_signed(
+          &system_instruction::create_account(
+              partner.key,
+              referrer_account.key,
+              Rent::get()?.minimum_balance(size),
+              size as u64,
+              program_id
+          ),
+          &[
+            partner.clone(),
+            referrer_account.clone(),
+          ],
+          &[&[b"referrer", partner.key.as_ref(), &[referrer.bump]]],
+      )?;
+
+      profile.serialize(&mut &mut referrer_account.data.borrow_mut()[..])?;
+
+      Ok(())
+  }
+  fn withdraw_referral_earnings(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey) -> ProgramResult {
+
+      let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+      let partner: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let referrer_account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+      let profile: ReferrerProfile = Self::referrer_profile(referrer_account, program_id)?;
+
+      if !partner.is_signer {panic!()}
+      if profile.referrer != partner.key.to_bytes() {panic!()}
+
+      //earned stays as the lifetime total, only the balance above rent is paid out
+      let r: u64 = Rent::get()?.minimum_balance(referrer_account.data_len());
+      let value: u64 = (**referrer_account.lamports.borrow()).saturating_sub(r);
+
+      **referrer_account.lamports.borrow_mut()-= value;
+      **partner.lamports.borrow_mut()+= value;
+
+      Ok(())
+  }
+  fn initialize_counter(        
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    t_counter: InitTournamentCounter) -> ProgramResult {
+
+      let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+      let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let tournament_counter: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+      let a_k: Pubkey = Pubkey::from_str("4YbLBRXwseG1NuyJbteSD5u81Q2QjFqJBp6JmxwYBKYm").unwrap();
+      if authority.key != &a_k {panic!()}
+      if !authority.is_signer {panic!()}
+
+      let counter_no_str: String = t_counter.counter_no.to_string();
+
+      if tournament_counter.owner == &ID && **tournament_counter.lamports.borrow() != 0{
+      let value: u64 = **tournament_counter.lamports.borrow(); 
+      invoke_signed(&system_instruction::transfer(tournament_counter.key, authority.key, value),
+              &[
+              authority.clone(), 
+              tournament_counter.clone(),
+          ],
+          &[&[b"count",counter_no_str.as_ref(), &[t_counter.bump]]])?;
+      }
+
+      invoke_signed(
+          &system_instruction::create_account(
+              &authority.key, 
+              &tournament_counter.key,
+              t_counter.rent,
+              24,
+              &program_id
+          ),
+          &[
+              authority.clone(), 
+              tournament_counter.clone(),
+          ],
+          &[&[b"count",counter_no_str.as_ref(), &[t_counter.bump]]], 
+      )?;
+
+      let counter: TournamentCounter = TournamentCounter{
+        empty:"E".to_string(),
+        counter_no:t_counter.counter_no,
+        player_participating:0,
+        capacity:t_counter.capacity,
+        tournament_id:t_counter.tournament_id,
+      };
+
+      counter.serialize(&mut &mut tournament_counter.data.borrow_mut()[..])?;
+
+
+      Ok(())
+  }
+  fn update_counter_finder(        
+    accounts: &[AccountInfo],
+    c_finder: CounterFinder) -> ProgramResult {
+  
+        let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+  
+        let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let finder: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+  
+        let a_k: Pubkey = Pubkey::from_str("4YbLBRXwseG1NuyJbteSD5u81Q2QjFqJBp6JmxwYBKYm").unwrap();
+        if authority.key != &a_k {panic!()}
+        if !authority.is_signer {panic!()}
+  
+      let f: CounterFinder = CounterFinder{
+        finder_no:c_finder.finder_no,
+        counters:c_finder.counters,
+        tournament_id:c_finder.tournament_id,
+      };
+
+      f.serialize(&mut &mut finder.data.borrow_mut()[..])?;
+
+        Ok(())
+  }
+  fn update_finder_finder(        
+        accounts: &[AccountInfo],
+        f_finder: FinderFinder) -> ProgramResult {
+    
+          let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+  
+          let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+          let finder: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+    
+          let a_k: Pubkey = Pubkey::from_str("4YbLBRXwseG1NuyJbteSD5u81Q2QjFqJBp6JmxwYBKYm").unwrap();
+          if authority.key != &a_k {panic!()}
+          if !authority.is_signer {panic!()}
+    
+        let f: FinderFinder = FinderFinder{
+          finder_no:f_finder.finder_no,
+          counters:f_finder.counters,
+          tournament_id:f_finder.tournament_id,
+        };
+  
+        f.serialize(&mut &mut finder.data.borrow_mut()[..])?;
+    
+          Ok(())
+  }
+  fn delete_account_authority(        
+    accounts: &[AccountInfo]) -> ProgramResult {
+
+      let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+      let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+      let account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+      let a_k: Pubkey = Pubkey::from_str("4YbLBRXwseG1NuyJbteSD5u81Q2QjFqJBp6JmxwYBKYm").unwrap();
+      if authority.key != &a_k {panic!()}
+      if !authority.is_signer {panic!()}
+
+      let value: u64 = **account.lamports.borrow();
+
+      **account.lamports.borrow_mut()-= value;
+      **authority.lamports.borrow_mut()+= value;
+
+      Ok(())
+  }
+  fn delete_account(        
+      accounts: &[AccountInfo]) -> ProgramResult {
+  
+        let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut accounts.iter();
+
+        let authority: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+        let account: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+  
+
+        if !account.is_signer {panic!()}
+
+        if !account.is_signer {panic!()}
+  
+        let value: u64 = **account.lamports.borrow();
+  
+        **account.lamports.borrow_mut()-= value;
+        **authority.lamports.borrow_mut()+= value;
+
+  
+        Ok(())
+  }
+  fn board_dimension(
+    size: u8) -> u8 {
+
+    if size == 0 {return CLASSIC_BOARD_SIZE;}
+
+    if size < MIN_BOARD_SIZE {panic!()}
+    if size > MAX_BOARD_SIZE {panic!()}
+
+    size
+  }
+  fn fleet_definition(
+    mut fleet: Vec<u8>,
+    width: u8,
+    height: u8) -> Vec<u8> {
+
+    if fleet.is_empty() {fleet = CLASSIC_FLEET.to_vec();}
+
+    if fleet.len() > MAX_FLEET_SHIPS {panic!()}
+
+    let mut ships_total: u16 = 0;
+    for length in fleet.iter() {
+      if *length < 1 {panic!()}
+      if *length > width && *length > height {panic!()}
+      ships_total += *length as u16;
+    }
+    if ships_total > (width as u16*height as u16)/2 {panic!()}
+
+    fleet
+  }
+  fn expand_fleet(
+    fleet: &Fleet,
+    definition: &[u8],
+    width: u8,
+    height: u8,
+    no_touching: u8) -> Result<Vec<u8>, ProgramError> {
+
+    if fleet.ships.len() != definition.len() {return Err(MissingShip.into());}
+
+    let mut cells: Vec<u8> = Vec::new();
+    let mut ship_of: Vec<usize> = Vec::new();
+
+    for (i, ship) in fleet.ships.iter().enumerate() {
+      if ship.length != definition[i] {return Err(FleetMismatch.into());}
+
+      for cell in Self::ship_cells(ship, width, height)? {
+        cells.push(cell);
+        ship_of.push(i);
+      }
+    }
+
+    Self::validate_fleet(&cells, &ship_of, width, no_touching)?;
+
+    Ok(cells)
+  }
+  fn ship_cells(
+    ship: &ShipPlacement,
+    width: u8,
+    height: u8) -> Result<Vec<u8>, ProgramError> {
+
+    if ship.axis != 1 && ship.axis != 2 {return Err(InvalidShipAxis.into());}
+
+    let mut x_end: u16 = ship.x as u16;
+    let mut y_end: u16 = ship.y as u16;
+    if ship.axis == 1 {x_end += ship.length as u16 - 1;}
+    if ship.axis == 2 {y_end += ship.length as u16 - 1;}
+
+    if ship.x < 1 || ship.y < 1 {return Err(ShipOutOfBounds.into());}
+    if x_end > width as u16 || y_end > height as u16 {return Err(ShipOutOfBounds.into());}
+
+    let mut cells: Vec<u8> = Vec::new();
+
+    for k in 0..ship.length {
+      if ship.axis == 1 {
+        cells.push((ship.x + k)+(ship.y*width));
+      }else{
+        cells.push(((ship.y + k)*width)+(ship.x));
+      }
+    }
+
+    Ok(cells)
+  }
+  fn ship_commitment(
+    ship: &ShipPlacement,
+    salt: &[u8;32]) -> [u8;32] {
+
+    keccak::hashv(&[salt, &[ship.length, ship.axis, ship.x, ship.y]]).0
+  }
+  fn ship_hash_buffer(
+    hashes: &[[u8;32]]) -> [[u8;32];MAX_FLEET_SHIPS] {
+
+    //callers check the count against the fleet first, so the hashes always fit
+    let mut buffer: [[u8;32];MAX_FLEET_SHIPS] = [[0;32];MAX_FLEET_SHIPS];
+    buffer[..hashes.len()].copy_from_slice(hashes);
+    buffer
+  }
+  fn ships_match_commitments(
+    fleet: &Fleet,
+    salts: &[[u8;32]],
+    commitments: &[[u8;32]]) -> bool {
+
+    if salts.len() != commitments.len() {return false;}
+    if fleet.ships.len() != commitments.len() {return false;}
+
+    for (i, ship) in fleet.ships.iter().enumerate() {
+      if Self::ship_commitment(ship, &salts[i]) != commitments[i] {return false;}
+    }
+
+    true
+  }
+  fn validate_fleet(
+    cells: &[u8],
+    ship_of: &[usize],
+    width: u8,
+    no_touching: u8) -> ProgramResult {
+
+    for i in 0..cells.len() {
+      for j in (i + 1)..cells.len() {
+        if cells[i] == cells[j] {return Err(OverlappingShips.into());}
+      }
+    }
+
+    if no_touching == 1 {
+      for i in 0..cells.len() {
+        for j in (i + 1)..cells.len() {
+          if ship_of[i] == ship_of[j] {continue;}
+
+          let iy: i16 = ((cells[i] - 1)/width) as i16;
+          let ix: i16 = cells[i] as i16 - iy*width as i16;
+          let jy: i16 = ((cells[j] - 1)/width) as i16;
+          let jx: i16 = cells[j] as i16 - jy*width as i16;
+
+          if (ix - jx).abs() <= 1 && (iy - jy).abs() <= 1 {return Err(TouchingShips.into());}
+        }
+      }
+    }
+
+    Ok(())
+  }
+  fn pay_winner(
+    state: &mut GameState,
+    winner: u8,
+    escrow: &AccountInfo,
+    initializer: &AccountInfo,
+    guest: &AccountInfo,
+    host: &AccountInfo,
+    referred: u64) -> ProgramResult {
+
+    let (host_fee, rew) = Self::win_split(state.lamports, state.host_fee_bps);
+
+    Self::escrow_out(&mut state.escrowed, escrow, host, host_fee - referred)?;
+
+    if winner == 1{
+      Self::escrow_out(&mut state.escrowed, escrow, initializer, rew)?;
+    }
+    if winner == 2{
+      Self::escrow_out(&mut state.escrowed, escrow, guest, rew)?;
+    }
+
+    Ok(())
+  }
+  fn pay_draw(
+    state: &mut GameState,
+    escrow: &AccountInfo,
+    initializer: &AccountInfo,
+    guest: &AccountInfo,
+    host: &AccountInfo,
+    referred: u64) -> ProgramResult {
+
+    let (host_fee, refund) = Self::draw_split(state.lamports, state.host_fee_bps);
+
+    Self::escrow_out(&mut state.escrowed, escrow, host, host_fee - referred)?;
+    Self::escrow_out(&mut state.escrowed, escrow, guest, refund)?;
+    Self::escrow_out(&mut state.escrowed, escrow, initializer, refund)?;
+
+    Ok(())
+  }
+  fn host_fee(
+    stake: u64,
+    bps: u16) -> u64 {
+
+    //charged on each stake separately and rounded down, so both sides always pay the same
+    ((stake as u128 * bps as u128) / 10_000) as u64
+  }
+  fn win_split(
+    stake: u64,
+    bps: u16) -> (u64, u64) {
+
+    //host fee and winner reward out of both stakes
+    let host_fee: u64 = Self::host_fee(stake, bps)*2;
+
+    (host_fee, stake*2 - host_fee)
+  }
+  fn draw_split(
+    stake: u64,
+    bps: u16) -> (u64, u64) {
+
+    //host fee out of both stakes and the refund each player gets back
+    let host_fee: u64 = Self::host_fee(stake, bps);
+
+    (host_fee*2, stake - host_fee)
+  }
+  fn transition(
+    status: &mut GameStatus,
+    next: GameStatus) -> ProgramResult {
+
+    if !status.can_become(next) {return Err(InvalidTransition.into());}
+    *status = next;
+
+    Ok(())
+  }
+  fn settle<'a>(
+    state: &mut GameState,
+    winner: u8,
+    reason: u8,
+    accounts: SettleAccounts<'_, 'a>) -> ProgramResult {
+
+    if state.best_of > 1 {
+      if winner == 1 {state.initializer_series_wins += 1;}
+      if winner == 2 {state.guest_series_wins += 1;}
+      state.series_games += 1;
+
+      let majority: bool = state.initializer_series_wins*2 > state.best_of || state.guest_series_wins*2 > state.best_of;
+
+      //drawn games count towards best_of, once all are played the series is settled on its score
+      if !majority && state.series_games >= state.best_of {
+        let mut leader: u8 = 3;
+        if state.initializer_series_wins > state.guest_series_wins {leader = 1;}
+        if state.guest_series_wins > state.initializer_series_wins {leader = 2;}
+        return Self::settle_wager(state, leader, RESULT_SERIES_SCORE, accounts);
+      }
+
+      //no majority yet, the wager stays escrowed until both sides commit the next game
+      if !majority {
+        Self::record_result(state, winner, reason)?;
+        state.whoseturn = 0;
+        state.draw_offer = 0;
+        state.series_commits = 0;
+        state.lastplaytime = Self::current_time(state.time_unit)?;
+        Self::transition(&mut state.status, GameStatus::BetweenGames)?;
+
+        state.serialize(&mut &mut accounts.game_state.data.borrow_mut()[..])?;
+
+        return Ok(());
+      }
+    }
+
+    Self::settle_wager(state, winner, reason, accounts)
+  }
+  fn settle_wager<'a>(
+    state: &mut GameState,
+    winner: u8,
+    reason: u8,
+    accounts: SettleAccounts<'_, 'a>) -> ProgramResult {
+
+    let SettleAccounts{game_state, initializer, guest, host, remaining} = accounts;
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'a>> = &mut remaining.iter();
+    let host_profile: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+
+    let mut outcome: GameStatus = GameStatus::Settled;
+    if reason == RESULT_TIME || reason == RESULT_FLAG_FALL || reason == RESULT_SERIES_FORFEIT || reason == RESULT_REVEAL_TIMEOUT {outcome = GameStatus::TimedOut;}
+
+    Self::record_result(state, winner, reason)?;
+    state.whoseturn = 0;
+    state.draw_offer = 0;
+    Self::transition(&mut state.status, outcome)?;
+
+    let mut referred: u64 = 0;
+    if state.mint != [0;32]{
+      Self::pay_tokens(state, winner, game_state, initializer, accounts_iter.as_slice())?;
+    }else{
+      let escrow: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+      Self::check_escrow(state.escrow_bump, state.escrowed, state.lamports*2, game_state, escrow, game_state.owner)?;
+
+      //each player's referrer takes its share out of the fee that player paid
+      let fee: u64 = Self::host_fee(state.lamports, state.host_fee_bps);
+      if state.initializer_referrer != [0;32] {
+        let referrer: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+        let share: u64 = Self::referral_share(referrer, &state.initializer_referrer, state.referral_share_bps, fee, game_state.owner)?;
+        Self::escrow_out(&mut state.escrowed, escrow, referrer, share)?;
+        referred += share;
+      }
+      if state.guest_referrer != [0;32] {
+        let referrer: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+        let share: u64 = Self::referral_share(referrer, &state.guest_referrer, state.referral_share_bps, fee, game_state.owner)?;
+        Self::escrow_out(&mut state.escrowed, escrow, referrer, share)?;
+        referred += share;
+      }
+
+      if winner == 3{
+        Self::pay_draw(state, escrow, initializer, guest, host, referred)?;
+      }else{
+        Self::pay_winner(state, winner, escrow, initializer, guest, host, referred)?;
+      }
+
+      //a kept record holds on to the escrow so a rematch can reuse it
+      if state.close_on_settle == 1 {
+        Self::close_escrow(state.escrowed, escrow, initializer)?;
+      }
+    }
+
+    //fees_earned is kept in lamports, token wagers only count towards games_hosted
+    let mut fee: u64 = 0;
+    if state.mint == [0;32] {fee = Self::host_fee(state.lamports, state.host_fee_bps)*2 - referred;}
+    Self::credit_host(host_profile, &state.host_profile, fee, game_state.owner)?;
+
+    state.serialize(&mut &mut game_state.data.borrow_mut()[..])?;
+
+    Self::close_or_keep(state, game_state, initializer)
+  }
+  fn check_fleet_down(
+    state: &mut GameState,
+    current_time: u64) -> ProgramResult {
+
+    let ships_total: u8 = state.fleet.iter().sum();
+
+    if state.ihits >= ships_total || state.ghits >= ships_total {
+      state.initializer_reveal = 0;
+      state.guest_reveal = 0;
+      state.reveal_deadline = current_time + state.move_timeout;
+      Self::transition(&mut state.status, GameStatus::AwaitingReveal)?;
+    }
+
+    Ok(())
+  }
+  fn board_matches(
+    game_arr: &[u8],
+    board: &[u8;256],
+    pending: &[u8]) -> (bool, u8) {
+
+    let mut consistent: bool = true;
+    let mut reported: usize = 0;
+    let mut pending_hits: u8 = 0;
+
+    for cell in game_arr.iter() {
+      let s: usize = *cell as usize;
+      if board[s] == 2 {reported += 1;}
+      if board[s] == 1 {
+        if pending.contains(cell) {pending_hits += 1;}
+        else {consistent = false;}
+      }
+    }
+
+    //a hit reported on water is as false as a miss reported on a ship
+    if board.iter().filter(|s| **s == 2).count() != reported {consistent = false;}
+
+    (consistent, pending_hits)
+  }
+  fn check_vault(
+    state: &GameState,
+    game_state: &AccountInfo,
+    vault: &AccountInfo,
+    token_program: &AccountInfo,
+    program_id: &Pubkey) -> ProgramResult {
+
+    let vault_check: Pubkey = Pubkey::create_program_address(&[b"vault", game_state.key.as_ref(), &[state.vault_bump]], program_id).unwrap();
+
+    if vault.key != &vault_check {panic!()}
+    if token_program.key != &spl_token::id() {panic!()}
+
+    Ok(())
+  }
+  fn check_token_account(
+    token_account: &AccountInfo,
+    owner: &[u8;32],
+    mint: &[u8;32]) -> ProgramResult {
+
+    if token_account.owner != &spl_token::id() {panic!()}
+
+    let account: spl_token::state::Account = spl_token::state::Account::unpack(&token_account.data.borrow())?;
+
+    if account.owner.to_bytes() != *owner {panic!()}
+    if account.mint.to_bytes() != *mint {panic!()}
+
+    Ok(())
+  }
+  fn open_vault<'a>(
+    payer: &AccountInfo<'a>,
+    game_state: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    bump: u8) -> ProgramResult {
+
+    let vault_check: Pubkey = Pubkey::create_program_address(&[b"vault", game_state.key.as_ref(), &[bump]], program_id).unwrap();
+
+    if vault.key != &vault_check {panic!()}
+    if token_program.key != &spl_token::id() {panic!()}
+
+    let size: usize = spl_token::state::Account::LEN;
+
+    invoke_signed(
+      &system_instruction::create_account(
+          payer.key,
+          vault.key,
+          Rent::get()?.minimum_balance(size),
+          size as u64,
+          &spl_token::id()
+      ),
+      &[
+        payer.clone(),
+        vault.clone(),
+      ],
+      &[&[b"vault", game_state.key.as_ref(), &[bump]]],
+    )?;
+
+    //the vault is its own authority so only this program can sign for it
+    invoke(&spl_token::instruction::initialize_account3(&spl_token::id(), vault.key, mint.key, vault.key)?,
+        &[
+          vault.clone(),
+          mint.clone(),
+          token_program.clone(),
+        ])?;
+
+    Ok(())
+  }
+  fn vault_deposit<'a>(
+    from: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    amount: u64) -> ProgramResult {
+
+    invoke(&spl_token::instruction::transfer(&spl_token::id(), from.key, vault.key, authority.key, &[], amount)?,
+        &[
+          from.clone(),
+          vault.clone(),
+          authority.clone(),
+          token_program.clone(),
+        ])?;
+
+    Ok(())
+  }
+  fn vault_withdraw<'a>(
+    state: &GameState,
+    game_state: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    to: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    amount: u64) -> ProgramResult {
+
+    invoke_signed(&spl_token::instruction::transfer(&spl_token::id(), vault.key, to.key, vault.key, &[], amount)?,
+        &[
+          vault.clone(),
+          to.clone(),
+          token_program.clone(),
+        ],
+        &[&[b"vault", game_state.key.as_ref(), &[state.vault_bump]]],
+    )?;
+
+    Ok(())
+  }
+  fn close_vault<'a>(
+    state: &GameState,
+    game_state: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>) -> ProgramResult {
+
+    invoke_signed(&spl_token::instruction::close_account(&spl_token::id(), vault.key, destination.key, vault.key, &[])?,
+        &[
+          vault.clone(),
+          destination.clone(),
+          token_program.clone(),
+        ],
+        &[&[b"vault", game_state.key.as_ref(), &[state.vault_bump]]],
+    )?;
+
+    Ok(())
+  }
+  fn pay_tokens<'a>(
+    state: &GameState,
+    winner: u8,
+    game_state: &AccountInfo<'a>,
+    initializer: &AccountInfo<'a>,
+    tokens: &[AccountInfo<'a>]) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'a>> = &mut tokens.iter();
+
+    let vault: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+    let initializer_token: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+    let guest_token: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+    let host_token: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+    let token_program: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+
+    Self::check_vault(state, game_state, vault, token_program, game_state.owner)?;
+    Self::check_token_account(initializer_token, &state.initializer, &state.mint)?;
+    Self::check_token_account(guest_token, &state.guest, &state.mint)?;
+    Self::check_token_account(host_token, &state.host, &state.mint)?;
+
+    if winner == 3{
+      let (host_fee, refund) = Self::draw_split(state.lamports, state.host_fee_bps);
+
+      Self::vault_withdraw(state, game_state, vault, host_token, token_program, host_fee)?;
+      Self::vault_withdraw(state, game_state, vault, initializer_token, token_program, refund)?;
+      Self::vault_withdraw(state, game_state, vault, guest_token, token_program, refund)?;
+    }else{
+      let (host_fee, rew) = Self::win_split(state.lamports, state.host_fee_bps);
+      let winner_token: &AccountInfo<'a> = if winner == 1 {initializer_token} else {guest_token};
+
+      Self::vault_withdraw(state, game_state, vault, host_token, token_program, host_fee)?;
+      Self::vault_withdraw(state, game_state, vault, winner_token, token_program, rew)?;
+    }
+
+    Self::close_vault(state, game_state, vault, initializer, token_program)
+  }
+  fn refund_vault<'a>(
+    state: &GameState,
+    game_state: &AccountInfo<'a>,
+    initializer: &AccountInfo<'a>,
+    tokens: &[AccountInfo<'a>],
+    program_id: &Pubkey) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'a>> = &mut tokens.iter();
+
+    let vault: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+    let initializer_token: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+    let token_program: &AccountInfo<'a> = next_account_info(accounts_iter)?;
+
+    Self::check_vault(state, game_state, vault, token_program, program_id)?;
+    Self::check_token_account(initializer_token, &state.initializer, &state.mint)?;
+
+    Self::vault_withdraw(state, game_state, vault, initializer_token, token_program, state.lamports)?;
+    Self::close_vault(state, game_state, vault, initializer, token_program)
+  }
+  fn open_escrow<'a>(
+    payer: &AccountInfo<'a>,
+    game_state: &AccountInfo<'a>,
+    escrow: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    bump: u8) -> ProgramResult {
+
+    let escrow_check: Pubkey = Pubkey::create_program_address(&[b"escrow", game_state.key.as_ref(), &[bump]], program_id).unwrap();
+
+    if escrow.key != &escrow_check {panic!()}
+
+    //anyone can send lamports to the address first, so only the missing rent is paid in
+    //and the account is taken over instead of created
+    let missing: u64 = Rent::get()?.minimum_balance(0).saturating_sub(**escrow.lamports.borrow());
+    if missing > 0 {
+      invoke(&system_instruction::transfer(payer.key, escrow.key, missing),
+          &[
+            payer.clone(),
+            escrow.clone(),
+          ])?;
+    }
+
+    //no data, the program only needs to own it to pay out of it
+    invoke_signed(&system_instruction::allocate(escrow.key, 0),
+        std::slice::from_ref(escrow),
+        &[&[b"escrow", game_state.key.as_ref(), &[bump]]],
+    )?;
+    invoke_signed(&system_instruction::assign(escrow.key, program_id),
+        std::slice::from_ref(escrow),
+        &[&[b"escrow", game_state.key.as_ref(), &[bump]]],
+    )?;
+
+    Ok(())
+  }
+  fn check_escrow(
+    bump: u8,
+    escrowed: u64,
+    expected: u64,
+    game_state: &AccountInfo,
+    escrow: &AccountInfo,
+    program_id: &Pubkey) -> ProgramResult {
+
+    let escrow_check: Pubkey = Pubkey::create_program_address(&[b"escrow", game_state.key.as_ref(), &[bump]], program_id).unwrap();
+
+    if escrow.key != &escrow_check {panic!()}
+    if escrow.owner != program_id {panic!()}
+    //the books have to match the stakes the game is holding at this point
+    if escrowed != expected {panic!()}
+
+    //anyone can send lamports in, so the balance may exceed the books but never fall short
+    if **escrow.lamports.borrow() < Rent::get()?.minimum_balance(0) + escrowed {panic!()}
+
+    Ok(())
+  }
+  fn escrow_in<'a>(
+    escrowed: &mut u64,
+    player: &AccountInfo<'a>,
+    escrow: &AccountInfo<'a>,
+    amount: u64) -> ProgramResult {
+
+    invoke(&system_instruction::transfer(player.key, escrow.key, amount),
+        &[
+          player.clone(),
+          escrow.clone(),
+        ])?;
+
+    *escrowed += amount;
+    msg!("escrow {} in {} from {}", escrow.key, amount, player.key);
+
+    Ok(())
+  }
+  fn escrow_out(
+    escrowed: &mut u64,
+    escrow: &AccountInfo,
+    to: &AccountInfo,
+    amount: u64) -> ProgramResult {
+
+    **escrow.lamports.borrow_mut()-= amount;
+    **to.lamports.borrow_mut()+= amount;
+
+    *escrowed -= amount;
+    msg!("escrow {} out {} to {}", escrow.key, amount, to.key);
+
+    Ok(())
+  }
+  fn close_escrow(
+    escrowed: u64,
+    escrow: &AccountInfo,
+    initializer: &AccountInfo) -> ProgramResult {
+
+    if escrowed != 0 {panic!()}
+
+    //the rent and anything sent in outside the books go back to the initializer
+    let value: u64 = **escrow.lamports.borrow();
+
+    **escrow.lamports.borrow_mut()-= value;
+    **initializer.lamports.borrow_mut()+= value;
+
+    Ok(())
+  }
+  fn refund_escrow(
+    state: &mut GameState,
+    game_state: &AccountInfo,
+    initializer: &AccountInfo,
+    remaining: &[AccountInfo],
+    program_id: &Pubkey) -> ProgramResult {
+
+    let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut remaining.iter();
+
+    let escrow: &AccountInfo<'_> = next_account_info(accounts_iter)?;
+
+    Self::check_escrow(state.escrow_bump, state.escrowed, state.lamports, game_state, escrow, program_id)?;
+
+    Self::escrow_out(&mut state.escrowed, escrow, initializer, state.lamports)?;
+    Self::close_escrow(state.escrowed, escrow, initializer)
+  }
+  fn record_result(
+    state: &mut GameState,
+    winner: u8,
+    reason: u8) -> ProgramResult {
+
+    state.result = GameResult{
+      winner,
+      reason,
+      initializer_score: state.ihits,
+      guest_score: state.ghits,
+      settled_at: Self::current_time(state.time_unit)?,
+    };
+
+    Ok(())
+  }
+  fn close_or_keep(
+    state: &GameState,
+    game_state: &AccountInfo,
+    initializer: &AccountInfo) -> ProgramResult {
+
+    if state.close_on_settle == 1 {
+      //the result only survives in the transaction log once the account is gone
+      msg!("game {} result {:?}", game_state.key, state.result);
+      game_state.data.borrow_mut().fill(0);
+
+      let value: u64 = **game_state.lamports.borrow();
+
+      **game_state.lamports.borrow_mut()-= value;
+      **initializer.lamports.borrow_mut()+= value;
+
+      return Ok(());
+    }
+
+    //not a compact record, the whole GameState with its boards stays rent-funded so a rematch can reuse it,
+    //everything above that reserve goes back to the initializer and the rest waits for CloseGame
+    let reserve: u64 = Rent::get()?.minimum_balance(game_state.data_len());
+    let value: u64 = (**game_state.lamports.borrow()).saturating_sub(reserve);
+
+    **game_state.lamports.borrow_mut()-= value;
+    **initializer.lamports.borrow_mut()+= value;
+
+    Ok(())
+  }
+  fn reset_game(
+    state: &mut GameState,
+    current_time: u64) -> ProgramResult {
+
+    state.first_turn = 3 - state.first_turn;
+    state.whoseturn = state.first_turn;
+    state.lastplaytime = current_time;
+    state.lastmove = 0;
+    state.last_salvo = [0;MAX_FLEET_SHIPS];
+    state.last_salvo_len = 0;
+    state.ishots = 0;
+    state.ihits = 0;
+    state.isunk = 0;
+    state.gshots = 0;
+    state.ghits = 0;
+    state.gsunk = 0;
+    state.initializer_sunk_mask = 0;
+    state.guest_sunk_mask = 0;
+    state.initializer_time_bank = state.time_bank;
+    state.guest_time_bank = state.time_bank;
+    state.initializer_board_state = [0;256];
+    state.guest_board_state = [0;256];
+    state.draw_offer = 0;
+    state.result = GameResult{winner: 0, reason: 0, initializer_score: 0, guest_score: 0, settled_at: 0};
+    state.series_commits = 0;
+    state.initializer_specials_used = 0;
+    state.guest_specials_used = 0;
+    state.last_weapon = WEAPON_SHOT;
+    state.special_log = [SpecialRecord::default();2*MAX_SPECIAL_WEAPONS as usize];
+    state.special_count = 0;
+    state.initializer_reveal = 0;
+    state.guest_reveal = 0;
+    state.reveal_deadline = 0;
+    Self::transition(&mut state.status, GameStatus::Active)
+  }
+  fn special_area(
+    weapon: u8,
+    cell: u8,
+    width: u8,
+    height: u8) -> Vec<u8> {
+
+    let y: u8 = (cell - 1)/width;
+    let x: u8 = cell - y*width;
+    let mut area: Vec<u8> = Vec::new();
+
+    if weapon == WEAPON_RADAR {
+      for ny in y.saturating_sub(1)..=y + 1 {
+        for nx in x.saturating_sub(1)..=x + 1 {
+          if nx >= 1 && nx <= width && ny >= 1 && ny <= height {area.push(nx + ny*width);}
+        }
+      }
+    }
+    if weapon == WEAPON_TORPEDO {
+      for nx in x..=width {area.push(nx + y*width);}
+    }
+    if weapon == WEAPON_AIRSTRIKE { // off-board arms stay in place as 0 so the answer bits line up
+      area.push(cell);
+      area.push(if x > 1 {cell - 1} else {0});
+      area.push(if x < width {cell + 1} else {0});
+      area.push(if y > 1 {cell - width} else {0});
+      area.push(if y < height {cell + width} else {0});
+    }
+
+    area
+  }
+  fn special_answer(
+    weapon: u8,
+    area: &[u8],
+    game_arr: &[u8]) -> u8 {
+
+    let mut answer: u8 = 0;
+
+    if weapon == WEAPON_RADAR {
+      answer = area.iter().filter(|c| game_arr.contains(c)).count() as u8;
+    }
+    if weapon == WEAPON_TORPEDO {
+      if let Some(k) = area.iter().position(|c| game_arr.contains(c)) {answer = k as u8 + 1;}
+    }
+    if weapon == WEAPON_AIRSTRIKE {
+      for (k, c) in area.iter().enumerate() {
+        if *c != 0 && game_arr.contains(c) {answer |= 1 << k;}
+      }
+    }
+
+    answer
+  }
+  fn resolve_special(
+    state: &mut GameState,
+    owner: u8,
+    answer: u8) -> Result<u8, ProgramError> {
+
+    let weapon: u8 = state.last_weapon;
+    let cell: u8 = state.lastmove;
+    let area: Vec<u8> = Self::special_area(weapon, cell, state.board_width, state.board_height);
+    let board: &mut [u8;256] = if owner == 1 {&mut state.initializer_board_state} else {&mut state.guest_board_state};
+    let mut hits: u8 = 0;
+
+    if weapon == WEAPON_RADAR && answer as usize > area.len() {panic!()}
+    if weapon == WEAPON_TORPEDO {
+      if answer as usize > area.len() {panic!()}
+      for (k, c) in area.iter().enumerate() {
+        let s: usize = *c as usize;
+        if answer != 0 && k + 1 == answer as usize {
+          if board[s] == 1 {panic!()}
+          if board[s] == 0 {board[s] = 2; hits += 1;}
+          break;
+        }
+        if board[s] == 2 {panic!()}
+        board[s] = 1;
+      }
+    }
+    if weapon == WEAPON_AIRSTRIKE {
+      if answer >= 1 << area.len() {panic!()}
+      for (k, c) in area.iter().enumerate() {
+        let bit: u8 = (answer >> k) & 1;
+        let s: usize = *c as usize;
+        if *c == 0 {
+          if bit != 0 {panic!()}
+          continue;
+        }
+        if board[s] == 0 {board[s] = 1 + bit; hits += bit;}
+        else if board[s] != 1 + bit {panic!()}
+      }
+    }
+
+    let n: usize = state.special_count as usize;
+    if n >= state.special_log.len() {panic!()}
+    state.special_log[n] = SpecialRecord{owner, weapon, cell, answer};
+    state.special_count += 1;
+
+    Ok(hits)
+  }
+  fn answer_pending_special(
+    state: &mut GameState,
+    owner: u8,
+    game_arr: &[u8]) -> Result<u8, ProgramError> {
+
+    //a special fired in the move that ended the game is answered by the revealed board, as finalize would
+    if state.salvo != 0 || state.last_weapon == WEAPON_SHOT || state.lastmove == 0 || state.whoseturn != owner {return Ok(0);}
+
+    let area: Vec<u8> = Self::special_area(state.last_weapon, state.lastmove, state.board_width, state.board_height);
+    let answer: u8 = Self::special_answer(state.last_weapon, &area, game_arr);
+
+    Self::resolve_special(state, owner, answer)
+  }
+  fn specials_match_board(
+    state: &GameState,
+    owner: u8,
+    game_arr: &[u8]) -> bool {
+
+    for record in state.special_log[..state.special_count as usize].iter().filter(|r| r.owner == owner) {
+      let area: Vec<u8> = Self::special_area(record.weapon, record.cell, state.board_width, state.board_height);
+      if Self::special_answer(record.weapon, &area, game_arr) != record.answer {return false;}
+    }
+
+    true
+  }
+  fn ffa_check_survivor(
+    state: &mut FfaGameState,
+    current_time: u64) -> ProgramResult {
+
+    let alive: u8 = ((1u16 << state.player_count) - 1) as u8 & !state.eliminated;
+
+    if alive.count_ones() == 1 {
+      state.reveals = [0;MAX_FFA_PLAYERS as usize];
+      state.reveal_deadline = current_time + state.move_timeout;
+      Self::transition(&mut state.status, GameStatus::AwaitingReveal)?;
+    }
+
+    Ok(())
+  }
+  fn next_ffa_seat(
+    state: &FfaGameState) -> u8 {
+
+    for k in 1..=state.player_count {
+      let seat: u8 = (state.whoseturn + k) % state.player_count;
+      if state.eliminated & (1 << seat) == 0 {return seat;}
+    }
+
+    state.whoseturn
+  }
+  fn charge_clock(
+    state: &mut GameState,
+    current_time: u64) {
+
+    if state.time_bank == 0 {return;}
+
+    let elapsed: u64 = current_time - state.lastplaytime;
+
+    if state.whoseturn == 1{
+      if elapsed >= state.initializer_time_bank {panic!()}
+      state.initializer_time_bank = (state.initializer_time_bank - elapsed).saturating_add(state.increment);
+    }
+    if state.whoseturn == 2{
+      if elapsed >= state.guest_time_bank {panic!()}
+      state.guest_time_bank = (state.guest_time_bank - elapsed).saturating_add(state.increment);
+    }
+  }
+  fn create_move_log<'a>(
+    payer: &AccountInfo<'a>,
+    game_state: &AccountInfo<'a>,
+    move_log: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    bump: u8) -> ProgramResult {
+
+    let move_log_check: Pubkey = Pubkey::create_program_address(&[b"log", game_state.key.as_ref(), &[bump]], program_id).unwrap();
+    if move_log.key != &move_log_check {panic!()}
+
+    let log: MoveLog = MoveLog{game: game_state.key.to_bytes(), records: Vec::new()};
+    let size: usize = log.try_to_vec()?.len();
+
+    invoke_signed(
+      &system_instruction::create_account(
+          payer.key,
+          move_log.key,
+          Rent::get()?.minimum_balance(size),
+          size as u64,
+          program_id
+      ),
+      &[
+        payer.clone(),
+        move_log.clone(),
+      ],
+      &[&[b"log", game_state.key.as_ref(), &[bump]]],
+    )?;
+
+    log.serialize(&mut &mut move_log.data.borrow_mut()[..])?;
+
+    Ok(())
+  }
+  fn append_move_log<'a>(
+    payer: &AccountInfo<'a>,
+    game_state: &AccountInfo<'a>,
+    move_log: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    records: Vec<MoveRecord>) -> ProgramResult {
+
+    if move_log.owner != program_id {panic!()}
+
+    let mut log: MoveLog = MoveLog::try_from_slice(&move_log.data.borrow())?;
+    if log.game != game_state.key.to_bytes() {panic!()}
+
+    log.records.extend(records);
+
+    let size: usize = log.try_to_vec()?.len();
+    let missing: u64 = Rent::get()?.minimum_balance(size).saturating_sub(**move_log.lamports.borrow());
+    if missing > 0 {
+      invoke(&system_instruction::transfer(payer.key, move_log.key, missing),
+          &[
+            payer.clone(),
+            move_log.clone(),
+          ])?;
+    }
+    move_log.realloc(size, false)?;
+
+    log.serialize(&mut &mut move_log.data.borrow_mut()[..])?;
+
+    Ok(())
+  }
+  fn host_profile(
+    profile_account: &AccountInfo,
+    program_id: &Pubkey) -> Result<HostProfile, ProgramError> {
+
+    if profile_account.owner != program_id {panic!()}
+
+    let profile: HostProfile = HostProfile::try_from_slice(&profile_account.data.borrow())?;
+    let profile_check: Pubkey = Pubkey::create_program_address(&[b"host", profile.operator.as_ref(), &[profile.bump]], program_id).unwrap();
+
+    if profile_account.key != &profile_check {panic!()}
+
+    Ok(profile)
+  }
+  fn credit_host(
+    profile_account: &AccountInfo,
+    profile_key: &[u8;32],
+    fee: u64,
+    program_id: &Pubkey) -> ProgramResult {
+
+    if profile_account.key.to_bytes() != *profile_key {panic!()}
+
+    let mut profile: HostProfile = Self::host_profile(profile_account, program_id)?;
+    profile.fees_earned += fee;
+    profile.serialize(&mut &mut profile_account.data.borrow_mut()[..])?;
+
+    Ok(())
+  }
+  fn referrer_profile(
+    referrer_account: &AccountInfo,
+    program_id: &Pubkey) -> Result<ReferrerProfile, ProgramError> {
+
+    if referrer_account.owner != program_id {panic!()}
+
+    let profile: ReferrerProfile = ReferrerProfile::try_from_slice(&referrer_account.data.borrow())?;
+    let referrer_check: Pubkey = Pubkey::create_program_address(&[b"referrer", profile.referrer.as_ref(), &[profile.bump]], program_id).unwrap();
+
+    if referrer_account.key != &referrer_check {panic!()}
+
+    Ok(profile)
+  }
+  fn refer(
+    player: &AccountInfo,
+    referrer_account: &AccountInfo,
+    program_id: &Pubkey) -> Result<[u8;32], ProgramError> {
+
+    let mut profile: ReferrerProfile = Self::referrer_profile(referrer_account, program_id)?;
+
+    if profile.referrer == player.key.to_bytes() {panic!()}
+
+    profile.games_referred += 1;
+    profile.serialize(&mut &mut referrer_account.data.borrow_mut()[..])?;
+
+    Ok(referrer_account.key.to_bytes())
+  }
+  fn pay_referral(
+    payer: &AccountInfo,
+    referrer_account: &AccountInfo,
+    referrer_key: &[u8;32],
+    share_bps: u16,
+    fee: u64,
+    program_id: &Pubkey) -> Result<u64, ProgramError> {
+
+    let share: u64 = Self::referral_share(referrer_account, referrer_key, share_bps, fee, program_id)?;
+
+    **payer.lamports.borrow_mut()-= share;
+    **referrer_account.lamports.borrow_mut()+= share;
+
+    Ok(share)
+  }
+  fn referral_share(
+    referrer_account: &AccountInfo,
+    referrer_key: &[u8;32],
+    share_bps: u16,
+    fee: u64,
+    program_id: &Pubkey) -> Result<u64, ProgramError> {
+
+    if referrer_account.key.to_bytes() != *referrer_key {panic!()}
+
+    let mut profile: ReferrerProfile = Self::referrer_profile(referrer_account, program_id)?;
+
+    //same rounding as the host fee, the referrer never gets more than the share of what was charged
+    let share: u64 = Self::host_fee(fee, share_bps);
+
+    profile.earned += share;
+    profile.serialize(&mut &mut referrer_account.data.borrow_mut()[..])?;
+
+    Ok(share)
+  }
+  fn program_config(
+    config_account: &AccountInfo,
+    program_id: &Pubkey) -> Result<ProgramConfig, ProgramError> {
+
+    if config_account.owner != program_id {panic!()}
+
+    let config: ProgramConfig = ProgramConfig::try_from_slice(&config_account.data.borrow())?;
+    let config_check: Pubkey = Pubkey::create_program_address(&[b"config", &[config.bump]], program_id).unwrap();
+
+    if config_account.key != &config_check {panic!()}
+
+    Ok(config)
+  }
+  fn current_time(
+    time_unit: u8) -> Result<u64, ProgramError> {
+
+    let clock: Clock= Clock::get()?;
+
+    if time_unit == 1 {return Ok(clock.slot);}
+
+    Ok(clock.unix_timestamp as u64)
+  }
+  fn ships_sunk(
+    cells: &[u8],
+    fleet: &[u8],
+    board: &[u8;256]) -> u8 {
+
+    let mut sunk: u8 = 0;
+    let mut start: usize = 0;
+
+    for length in fleet.iter() {
+      let end: usize = start + *length as usize;
+      if cells[start..end].iter().all(|cell| board[*cell as usize] == 2) {
+        sunk += 1;
+      }
+      start = end;
+    }
+
+    sunk
+  }
+  fn verify_cell_report(
+    root: &[u8;32],
+    cell: u8,
+    hit: u8,
+    salt: &[u8;32],
+    proof: &[[u8;32]]) -> bool {
+
+    if proof.len() != BOARD_TREE_DEPTH {return false;}
+
+    let mut node: [u8;32] = keccak::hashv(&[&[cell], &[hit], salt]).0;
+    let mut index: usize = cell as usize;
+
+    for sibling in proof.iter() {
+      if index & 1 == 0 {
+        node = keccak::hashv(&[&node, sibling]).0;
+      }else{
+        node = keccak::hashv(&[sibling, &node]).0;
+      }
+      index /= 2;
+    }
+
+    &node == root
+  }
+
+}
+
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const STAKES: [u64; 5] = [1, 9_999, 10_000, 1_000_000_007, u64::MAX / 4];
+  const FEES: [u16; 5] = [0, 1, 250, 9_999, 10_000];
+
+  fn game(stake: u64, bps: u16) -> GameState {
+    let mut state: GameState = GameState::deserialize(&mut &vec![0u8; 10_000][..]).unwrap();
+    state.lamports = stake;
+    state.host_fee_bps = bps;
+    state.escrowed = stake*2;
+    state
+  }
+
+  //balances of escrow, initializer, guest and host after paying out a game
+  fn pay(winner: u8, stake: u64, bps: u16, referred: u64) -> (GameState, [u64; 4]) {
+    let mut state: GameState = game(stake, bps);
+    let owner: Pubkey = Pubkey::new_unique();
+    let keys: [Pubkey; 4] = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+    let mut lamports: [u64; 4] = [stake*2 - referred, 0, 0, 0];
+    let mut data: [[u8; 0]; 4] = [[]; 4];
+
+    {
+      let [l0, l1, l2, l3] = &mut lamports;
+      let [d0, d1, d2, d3] = &mut data;
+      let escrow: AccountInfo = AccountInfo::new(&keys[0], false, true, l0, d0, &owner, false, 0);
+      let initializer: AccountInfo = AccountInfo::new(&keys[1], false, true, l1, d1, &owner, false, 0);
+      let guest: AccountInfo = AccountInfo::new(&keys[2], false, true, l2, d2, &owner, false, 0);
+      let host: AccountInfo = AccountInfo::new(&keys[3], false, true, l3, d3, &owner, false, 0);
+
+      //the referral share has already left the escrow by the time the split is paid
+      state.escrowed -= referred;
+      if winner == 3 {
+        Processor::pay_draw(&mut state, &escrow, &initializer, &guest, &host, referred).unwrap();
+      } else {
+        Processor::pay_winner(&mut state, winner, &escrow, &initializer, &guest, &host, referred).unwrap();
+      }
+    }
+
+    (state, lamports)
+  }
+
+  #[test]
+  fn largest_salvo_fits_a_transaction() {
+    use solana_program::instruction::{AccountMeta, Instruction};
+    use solana_program::message::Message;
+    use crate::state::Salvo;
+
+    //every target the fleet allows plus as many proven reports as one move may carry
+    let salvo: Salvo = Salvo{
+      targets: vec![255; MAX_FLEET_SHIPS],
+      opponents_last_salvo_hits: vec![1; MAX_PROVEN_REPORTS],
+      ships_sunk: 0,
+      salts: vec![[7; 32]; MAX_PROVEN_REPORTS],
+      proofs: vec![vec![[9; 32]; BOARD_TREE_DEPTH]; MAX_PROVEN_REPORTS],
+    };
+    let mut data: Vec<u8> = vec![19];
+    data.extend(salvo.try_to_vec().unwrap());
+
+    let mover: Pubkey = Pubkey::new_unique();
+    let accounts: Vec<AccountMeta> = vec![
+      AccountMeta::new(mover, true),
+      AccountMeta::new_readonly(Pubkey::new_unique(), false),
+      AccountMeta::new_readonly(Pubkey::new_unique(), false),
+      AccountMeta::new(Pubkey::new_unique(), false),
+      AccountMeta::new(Pubkey::new_unique(), false),
+      AccountMeta::new_readonly(ID, false),
+    ];
+    let message: Message = Message::new(&[Instruction::new_with_bytes(Pubkey::new_unique(), &data, accounts)], Some(&mover));
+
+    //one signature and its length prefix ahead of the message, 1232 bytes is the packet limit
+    assert!(1 + 64 + message.serialize().len() <= 1232);
+  }
+
+  #[test]
+  fn host_fee_bounds() {
+    for stake in STAKES {
+      assert_eq!(Processor::host_fee(stake, 0), 0);
+      assert_eq!(Processor::host_fee(stake, 10_000), stake);
+      assert_eq!(Processor::host_fee(stake, 1), stake / 10_000);
+    }
+    assert_eq!(Processor::host_fee(u64::MAX, 10_000), u64::MAX);
+  }
+
+  #[test]
+  fn host_fee_one_lamport_stake() {
+    assert_eq!(Processor::host_fee(1, 0), 0);
+    assert_eq!(Processor::host_fee(1, 1), 0);
+    assert_eq!(Processor::host_fee(1, 9_999), 0);
+    assert_eq!(Processor::host_fee(1, 10_000), 1);
+  }
+
+  #[test]
+  fn host_fee_rounds_each_stake() {
+    //1.5 lamports on each side round down to 1, a fee on the pooled 3 would not
+    assert_eq!(Processor::host_fee(15_000, 1), 1);
+    assert_eq!(Processor::win_split(15_000, 1), (2, 29_998));
+    assert_eq!(Processor::draw_split(15_000, 1), (2, 14_999));
+
+    //both players pay the same fee whatever the outcome
+    for stake in STAKES {
+      for bps in FEES {
+        let fee: u64 = Processor::host_fee(stake, bps);
+        assert_eq!(Processor::win_split(stake, bps).0, fee*2);
+        assert_eq!(Processor::draw_split(stake, bps).0, fee*2);
+      }
+    }
+  }
+
+  #[test]
+  fn splits_conserve_the_pot() {
+    for stake in STAKES {
+      for bps in FEES {
+        let (host_fee, rew) = Processor::win_split(stake, bps);
+        assert_eq!(host_fee + rew, stake*2);
+
+        let (host_fee, refund) = Processor::draw_split(stake, bps);
+        assert_eq!(host_fee + refund*2, stake*2);
+      }
+    }
+    assert_eq!(Processor::win_split(1, 10_000), (2, 0));
+    assert_eq!(Processor::draw_split(1, 10_000), (2, 0));
+    assert_eq!(Processor::win_split(1, 0), (0, 2));
+    assert_eq!(Processor::draw_split(1, 0), (0, 1));
+  }
+
+  #[test]
+  fn pay_winner_pays_either_side() {
+    for stake in STAKES {
+      for bps in FEES {
+        let (host_fee, rew) = Processor::win_split(stake, bps);
+
+        let (state, lamports) = pay(1, stake, bps, 0);
+        assert_eq!(lamports, [0, rew, 0, host_fee]);
+        assert_eq!(state.escrowed, 0);
+
+        let (state, lamports) = pay(2, stake, bps, 0);
+        assert_eq!(lamports, [0, 0, rew, host_fee]);
+        assert_eq!(state.escrowed, 0);
+      }
+    }
+  }
+
+  #[test]
+  fn pay_draw_refunds_both_sides() {
+    for stake in STAKES {
+      for bps in FEES {
+        let (host_fee, refund) = Processor::draw_split(stake, bps);
+
+        let (state, lamports) = pay(3, stake, bps, 0);
+        assert_eq!(lamports, [0, refund, refund, host_fee]);
+        assert_eq!(state.escrowed, 0);
+      }
+    }
+  }
+
+  #[test]
+  fn referral_comes_out_of_the_host_fee() {
+    let (state, lamports) = pay(1, 1_000_000, 250, 5_000);
+    assert_eq!(lamports, [0, 1_950_000, 0, 45_000]);
+    assert_eq!(state.escrowed, 0);
+
+    let (state, lamports) = pay(3, 1_000_000, 250, 5_000);
+    assert_eq!(lamports, [0, 975_000, 975_000, 45_000]);
+    assert_eq!(state.escrowed, 0);
+  }
+
+  //every level of a board tree, leaves first, with the hit bit of each cell under one salt
+  fn board_tree(hits: &[u8; 256], salt: &[u8; 32]) -> Vec<Vec<[u8; 32]>> {
+    let mut levels: Vec<Vec<[u8; 32]>> = vec![(0..256).map(|c| keccak::hashv(&[&[c as u8], &[hits[c]], salt]).0).collect()];
+    while levels[levels.len() - 1].len() > 1 {
+      let below: &Vec<[u8; 32]> = &levels[levels.len() - 1];
+      let level: Vec<[u8; 32]> = below.chunks(2).map(|pair| keccak::hashv(&[&pair[0], &pair[1]]).0).collect();
+      levels.push(level);
+    }
+    levels
+  }
+
+  fn board_proof(levels: &[Vec<[u8; 32]>], cell: u8) -> Vec<[u8; 32]> {
+    let mut index: usize = cell as usize;
+    let mut proof: Vec<[u8; 32]> = Vec::new();
+    for level in &levels[..BOARD_TREE_DEPTH] {
+      proof.push(level[index ^ 1]);
+      index /= 2;
+    }
+    proof
+  }
+
+  #[test]
+  fn cell_report_verifies_against_the_root() {
+    let salt: [u8; 32] = [7; 32];
+    let mut hits: [u8; 256] = [0; 256];
+    hits[12] = 1;
+    hits[13] = 1;
+    let levels: Vec<Vec<[u8; 32]>> = board_tree(&hits, &salt);
+    let root: [u8; 32] = levels[BOARD_TREE_DEPTH][0];
+
+    for cell in [0, 12, 13, 14, 110, 255] {
+      let proof: Vec<[u8; 32]> = board_proof(&levels, cell);
+      assert!(Processor::verify_cell_report(&root, cell, hits[cell as usize], &salt, &proof));
+    }
+  }
+
+  #[test]
+  fn tampered_cell_report_fails() {
+    let salt: [u8; 32] = [7; 32];
+    let mut hits: [u8; 256] = [0; 256];
+    hits[12] = 1;
+    let levels: Vec<Vec<[u8; 32]>> = board_tree(&hits, &salt);
+    let root: [u8; 32] = levels[BOARD_TREE_DEPTH][0];
+    let proof: Vec<[u8; 32]> = board_proof(&levels, 12);
+
+    //a miss reported on a committed ship cell
+    assert!(!Processor::verify_cell_report(&root, 12, 0, &salt, &proof));
+    //the proof of one cell replayed for its neighbour
+    assert!(!Processor::verify_cell_report(&root, 13, 1, &salt, &proof));
+    assert!(!Processor::verify_cell_report(&root, 12, 1, &[8; 32], &proof));
+
+    let mut forged: Vec<[u8; 32]> = proof.clone();
+    forged[3][0] ^= 1;
+    assert!(!Processor::verify_cell_report(&root, 12, 1, &salt, &forged));
+
+    //a proof must reach the root from the leaf level, not from a node above it
+    assert!(!Processor::verify_cell_report(&root, 12, 1, &salt, &proof[..BOARD_TREE_DEPTH - 1]));
+    assert!(!Processor::verify_cell_report(&root, 6, 1, &salt, &proof[1..]));
+  }
+
+  #[test]
+  fn board_matches_counts_pending_shots() {
+    let game_arr: [u8; 3] = [12, 13, 14];
+    let mut board: [u8; 256] = [0; 256];
+    board[12] = 2;
+    board[13] = 1;
+    board[60] = 1;
+
+    //a shot still waiting for its report is not a false miss
+    assert_eq!(Processor::board_matches(&game_arr, &board, &[13]), (true, 1));
+    assert_eq!(Processor::board_matches(&game_arr, &board, &[13, 60]), (true, 1));
+    assert_eq!(Processor::board_matches(&game_arr, &board, &[]), (false, 0));
+    assert_eq!(Processor::board_matches(&game_arr, &board, &[14]), (false, 0));
+
+    //a hit reported on water
+    board[50] = 2;
+    assert_eq!(Processor::board_matches(&game_arr, &board, &[13]), (false, 1));
+  }
+
+  fn ship(length: u8, axis: u8, x: u8, y: u8) -> ShipPlacement {
+    ShipPlacement {length, axis, x, y}
+  }
+
+  //the classic fleet on every other row, the last ship tucked into the far corner
+  fn classic_fleet() -> Fleet {
+    Fleet {ships: vec![ship(5, 1, 1, 1), ship(4, 1, 1, 3), ship(3, 1, 1, 5), ship(3, 1, 1, 7), ship(2, 1, 9, 10)]}
+  }
+
+  fn expand(fleet: &Fleet, no_touching: u8) -> Result<Vec<u8>, ProgramError> {
+    Processor::expand_fleet(fleet, &CLASSIC_FLEET, CLASSIC_BOARD_SIZE, CLASSIC_BOARD_SIZE, no_touching)
+  }
+
+  #[test]
+  fn expand_fleet_accepts_a_legal_fleet() {
+    let cells: Vec<u8> = expand(&classic_fleet(), 1).unwrap();
+    assert_eq!(cells.len(), 17);
+    assert_eq!(&cells[..5], &[11, 12, 13, 14, 15]);
+    assert_eq!(&cells[15..], &[109, 110]);
+
+    let mut fleet: Fleet = classic_fleet();
+    fleet.ships[0] = ship(5, 2, 10, 1);
+    assert_eq!(&expand(&fleet, 1).unwrap()[..5], &[20, 30, 40, 50, 60]);
+  }
+
+  #[test]
+  fn expand_fleet_rejects_each_placement_error() {
+    let mut fleet: Fleet = classic_fleet();
+    fleet.ships.pop();
+    assert_eq!(expand(&fleet, 0), Err(MissingShip.into()));
+
+    let mut fleet: Fleet = classic_fleet();
+    fleet.ships.swap(0, 1);
+    assert_eq!(expand(&fleet, 0), Err(FleetMismatch.into()));
+
+    for axis in [0, 3] {
+      let mut fleet: Fleet = classic_fleet();
+      fleet.ships[2].axis = axis;
+      assert_eq!(expand(&fleet, 0), Err(InvalidShipAxis.into()));
+    }
+
+    for placement in [ship(5, 1, 0, 1), ship(5, 1, 1, 0), ship(5, 1, 7, 1), ship(5, 2, 1, 7), ship(5, 1, 1, 11)] {
+      let mut fleet: Fleet = classic_fleet();
+      fleet.ships[0] = placement;
+      assert_eq!(expand(&fleet, 0), Err(ShipOutOfBounds.into()));
+    }
+
+    let mut fleet: Fleet = classic_fleet();
+    fleet.ships[1] = ship(4, 2, 3, 1);
+    assert_eq!(expand(&fleet, 0), Err(OverlappingShips.into()));
+  }
+
+  #[test]
+  fn touching_ships_only_fail_when_the_rule_is_on() {
+    //side by side on neighbouring rows, then corner to corner
+    for placement in [ship(4, 1, 1, 2), ship(4, 1, 6, 2)] {
+      let mut fleet: Fleet = classic_fleet();
+      fleet.ships[1] = placement;
+      assert_eq!(expand(&fleet, 1), Err(TouchingShips.into()));
+      assert!(expand(&fleet, 0).is_ok());
+    }
+
+    //cells 20 and 21 are numbered next to each other but sit on opposite edges of the board
+    let mut fleet: Fleet = classic_fleet();
+    fleet.ships[0] = ship(5, 1, 6, 1);
+    fleet.ships[1] = ship(4, 1, 1, 2);
+    assert!(expand(&fleet, 1).is_ok());
+  }
+
+  #[test]
+  fn ships_sunk_needs_every_cell_hit() {
+    let cells: Vec<u8> = expand(&classic_fleet(), 1).unwrap();
+    let mut board: [u8; 256] = [0; 256];
+    assert_eq!(Processor::ships_sunk(&cells, &CLASSIC_FLEET, &board), 0);
+
+    board[109] = 2;
+    board[110] = 2;
+    for cell in &cells[..4] {board[*cell as usize] = 2;}
+    assert_eq!(Processor::ships_sunk(&cells, &CLASSIC_FLEET, &board), 1);
+
+    //a miss reported on the last cell does not sink it
+    board[15] = 1;
+    assert_eq!(Processor::ships_sunk(&cells, &CLASSIC_FLEET, &board), 1);
+
+    for cell in cells.iter() {board[*cell as usize] = 2;}
+    assert_eq!(Processor::ships_sunk(&cells, &CLASSIC_FLEET, &board), 5);
+  }
+
+  fn ffa(player_count: u8, whoseturn: u8, eliminated: u8) -> FfaGameState {
+    let mut state: FfaGameState = FfaGameState::deserialize(&mut &vec![0u8; 10_000][..]).unwrap();
+    state.player_count = player_count;
+    state.whoseturn = whoseturn;
+    state.eliminated = eliminated;
+    state
+  }
+
+  #[test]
+  fn next_ffa_seat_skips_eliminated_players() {
+    assert_eq!(Processor::next_ffa_seat(&ffa(3, 0, 0)), 1);
+    assert_eq!(Processor::next_ffa_seat(&ffa(3, 2, 0)), 0);
+    assert_eq!(Processor::next_ffa_seat(&ffa(4, 3, 0)), 0);
+
+    assert_eq!(Processor::next_ffa_seat(&ffa(4, 0, 0b0010)), 2);
+    assert_eq!(Processor::next_ffa_seat(&ffa(4, 0, 0b0110)), 3);
+    assert_eq!(Processor::next_ffa_seat(&ffa(4, 2, 0b1001)), 1);
+
+    //the shooter who just knocked themselves out passes the turn on
+    assert_eq!(Processor::next_ffa_seat(&ffa(4, 1, 0b0011)), 2);
+    //with every other seat out the turn stays put
+    assert_eq!(Processor::next_ffa_seat(&ffa(3, 1, 0b0101)), 1);
+  }
+
+  #[test]
+  fn game_status_transitions() {
+    use GameStatus::*;
+    let all: [GameStatus; 8] = [Uninitialized, Open, Active, AwaitingReveal, Settled, Aborted, TimedOut, BetweenGames];
+    let allowed: [(GameStatus, GameStatus); 14] = [
+      (Uninitialized, Open),
+      (Open, Active), (Open, Aborted),
+      (Active, AwaitingReveal), (Active, Settled), (Active, TimedOut), (Active, BetweenGames),
+      (AwaitingReveal, Settled), (AwaitingReveal, TimedOut), (AwaitingReveal, BetweenGames),
+      (BetweenGames, Active), (BetweenGames, TimedOut),
+      (Settled, Active), (TimedOut, Active),
+    ];
+
+    for from in all {
+      for to in all {
+        assert_eq!(from.can_become(to), allowed.contains(&(from, to)), "{:?} -> {:?}", from, to);
+      }
+    }
+    //an aborted game never comes back
+    assert!(all.iter().all(|to| !Aborted.can_become(*to)));
+  }
+
+  #[test]
+  fn special_area_clips_at_the_board_edges() {
+    let size: u8 = CLASSIC_BOARD_SIZE;
+
+    assert_eq!(Processor::special_area(WEAPON_RADAR, 55, size, size), vec![44, 45, 46, 54, 55, 56, 64, 65, 66]);
+    assert_eq!(Processor::special_area(WEAPON_RADAR, 11, size, size), vec![11, 12, 21, 22]);
+    assert_eq!(Processor::special_area(WEAPON_RADAR, 60, size, size), vec![49, 50, 59, 60, 69, 70]);
+    assert_eq!(Processor::special_area(WEAPON_RADAR, 110, size, size), vec![99, 100, 109, 110]);
+
+    assert_eq!(Processor::special_area(WEAPON_TORPEDO, 55, size, size), vec![55, 56, 57, 58, 59, 60]);
+    assert_eq!(Processor::special_area(WEAPON_TORPEDO, 11, size, size), (11..=20).collect::<Vec<u8>>());
+    assert_eq!(Processor::special_area(WEAPON_TORPEDO, 20, size, size), vec![20]);
+
+    //center, west, east, north, south with off-board arms left as 0
+    assert_eq!(Processor::special_area(WEAPON_AIRSTRIKE, 55, size, size), vec![55, 54, 56, 45, 65]);
+    assert_eq!(Processor::special_area(WEAPON_AIRSTRIKE, 11, size, size), vec![11, 0, 12, 0, 21]);
+    assert_eq!(Processor::special_area(WEAPON_AIRSTRIKE, 110, size, size), vec![110, 109, 0, 100, 0]);
+
+    //a board wider than it is tall
+    assert_eq!(Processor::special_area(WEAPON_AIRSTRIKE, 48, 8, 5), vec![48, 47, 0, 40, 0]);
+    assert_eq!(Processor::special_area(WEAPON_RADAR, 48, 8, 5), vec![39, 40, 47, 48]);
+    assert_eq!(Processor::special_area(WEAPON_TORPEDO, 41, 8, 5), (41..=48).collect::<Vec<u8>>());
+  }
+
+  #[test]
+  fn special_answer_reads_the_area() {
+    let game_arr: [u8; 5] = [12, 21, 57, 58, 109];
+    let size: u8 = CLASSIC_BOARD_SIZE;
+    let answer = |weapon: u8, cell: u8| Processor::special_answer(weapon, &Processor::special_area(weapon, cell, size, size), &game_arr);
+
+    assert_eq!(answer(WEAPON_RADAR, 11), 2);
+    assert_eq!(answer(WEAPON_RADAR, 110), 1);
+    assert_eq!(answer(WEAPON_RADAR, 35), 0);
+
+    //cells travelled to the first ship cell, not the number of ship cells on the row
+    assert_eq!(answer(WEAPON_TORPEDO, 55), 3);
+    assert_eq!(answer(WEAPON_TORPEDO, 58), 1);
+    assert_eq!(answer(WEAPON_TORPEDO, 59), 0);
+    assert_eq!(answer(WEAPON_TORPEDO, 61), 0);
+
+    assert_eq!(answer(WEAPON_AIRSTRIKE, 11), 0b10100);
+    assert_eq!(answer(WEAPON_AIRSTRIKE, 57), 0b00101);
+    assert_eq!(answer(WEAPON_AIRSTRIKE, 110), 0b00010);
+
+    //an off-board arm never answers, even against a zeroed board slot
+    assert_eq!(Processor::special_answer(WEAPON_AIRSTRIKE, &[11, 0, 12, 0, 21], &[0, 12]), 0b00100);
+  }
+}
@@ -1,36 +1,136 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum GameStatus {
+    Uninitialized,
+    Open,
+    Active,
+    AwaitingReveal,
+    Settled,
+    Aborted,
+    TimedOut,
+    BetweenGames,
+}
+
+impl GameStatus {
+    //the only status changes a handler may make, a rematch reopens a finished game
+    pub fn can_become(&self, next: GameStatus) -> bool {
+        use GameStatus::*;
+        matches!((*self, next),
+            (Uninitialized, Open) |
+            (Open, Active) | (Open, Aborted) |
+            (Active, AwaitingReveal) | (Active, Settled) | (Active, TimedOut) | (Active, BetweenGames) |
+            (AwaitingReveal, Settled) | (AwaitingReveal, TimedOut) | (AwaitingReveal, BetweenGames) |
+            (BetweenGames, Active) | (BetweenGames, TimedOut) |
+            (Settled, Active) | (TimedOut, Active)
+        )
+    }
+    pub fn is_finished(&self) -> bool {
+        *self == GameStatus::Settled || *self == GameStatus::TimedOut
+    }
+}
+
+
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct GameState {
     pub host:[u8;32],
-    pub waiting:u8,
-    pub initialized:u8,
+    pub host_profile:[u8;32],
+    pub status:GameStatus,
     pub gameseed:String,
     pub lamports:u64,
+    pub host_fee_bps:u16,
+    pub referral_share_bps:u16,
+    pub initializer_referrer:[u8;32],
+    pub guest_referrer:[u8;32],
     pub initializer: [u8;32],
     pub initializergamehash: [u8;32],
     pub guest: [u8;32],
     pub guestgamehash: [u8;32],
+    pub initializer_board_root: [u8;32],
+    pub guest_board_root: [u8;32],
+    pub no_touching:u8,
+    pub fleet:Vec<u8>,
+    pub board_width:u8,
+    pub board_height:u8,
+    pub salvo:u8,
+    pub move_timeout:u64,
+    pub time_unit:u8,
+    pub time_bank:u64,
+    pub increment:u64,
+    pub initializer_time_bank:u64,
+    pub guest_time_bank:u64,
+    pub draw_offer:u8,
+    pub result:GameResult,
+    pub close_on_settle:u8,
+    pub expires_at:u64,
+    pub mint:[u8;32],
+    pub vault_bump:u8,
+    pub escrow_bump:u8,
+    pub escrowed:u64,
+    pub rematch_offer:u8,
+    pub first_turn:u8,
+    pub best_of:u8,
+    pub initializer_series_wins:u8,
+    pub guest_series_wins:u8,
+    pub series_commits:u8,
+    pub series_games:u8,
+    pub special_weapons:u8,
+    pub initializer_specials_used:u8,
+    pub guest_specials_used:u8,
+    pub last_weapon:u8,
+    pub special_log:[SpecialRecord;2*MAX_SPECIAL_WEAPONS as usize],
+    pub special_count:u8,
+    pub reveal_deadline:u64,
+    pub initializer_reveal:u8,
+    pub guest_reveal:u8,
+    pub invited_guest:[u8;32],
+    pub join_key:[u8;32],
+    pub log_moves:u8,
     pub whoseturn:u8,
     pub lastplaytime:u64,
     pub lastmove:u8,
+    pub last_salvo:[u8;MAX_FLEET_SHIPS],
+    pub last_salvo_len:u8,
     pub ishots:u8,
     pub ihits:u8,
+    pub isunk:u8,
     pub gshots:u8,
     pub ghits:u8,
-    pub initializer_board_state:[u8;128],
-    pub guest_board_state:[u8;128],
+    pub gsunk:u8,
+    pub initializer_ship_hashes:[[u8;32];MAX_FLEET_SHIPS],
+    pub initializer_ship_hash_count:u8,
+    pub guest_ship_hashes:[[u8;32];MAX_FLEET_SHIPS],
+    pub guest_ship_hash_count:u8,
+    pub initializer_sunk_mask:u16,
+    pub guest_sunk_mask:u16,
+    pub initializer_board_state:[u8;256],
+    pub guest_board_state:[u8;256],
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct GameResult{
+    pub winner:u8,
+    pub reason:u8,
+    pub initializer_score:u8,
+    pub guest_score:u8,
+    pub settled_at:u64,
 }
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct TGameState {
     pub game_id:String,
-    pub initialized:u8,
+    pub status:GameStatus,
     pub gameseed:String,
     pub lamports:u64,
     pub initializer: [u8;32],
     pub initializergamehash: [u8;32],
     pub guest: [u8;32],
     pub guestgamehash: [u8;32],
+    pub initializer_board_root: [u8;32],
+    pub guest_board_root: [u8;32],
+    pub no_touching:u8,
+    pub fleet:Vec<u8>,
+    pub board_width:u8,
+    pub board_height:u8,
+    pub log_moves:u8,
     pub whoseturn:u8,
     pub lastplaytime:u64,
     pub lastmove:u8,
@@ -38,8 +138,40 @@ pub struct TGameState {
     pub ihits:u8,
     pub gshots:u8,
     pub ghits:u8,
-    pub initializer_board_state:[u8;128],
-    pub guest_board_state:[u8;128],
+    pub initializer_board_state:[u8;256],
+    pub guest_board_state:[u8;256],
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct FfaGameState {
+    pub host:[u8;32],
+    pub host_profile:[u8;32],
+    pub status:GameStatus,
+    pub gameseed:String,
+    pub lamports:u64,
+    pub escrow_bump:u8,
+    pub escrowed:u64,
+    pub host_fee_bps:u16,
+    pub player_count:u8,
+    pub no_touching:u8,
+    pub fleet:Vec<u8>,
+    pub board_width:u8,
+    pub board_height:u8,
+    pub move_timeout:u64,
+    pub joined:u8,
+    pub players:[[u8;32];MAX_FFA_PLAYERS as usize],
+    pub game_hashes:[[u8;32];MAX_FFA_PLAYERS as usize],
+    pub board_roots:[[u8;32];MAX_FFA_PLAYERS as usize],
+    pub eliminated:u8,
+    pub whoseturn:u8,
+    pub lastplaytime:u64,
+    pub winner:u8,
+    pub pending:[[u8;FFA_PENDING_CAPACITY];MAX_FFA_PLAYERS as usize],
+    pub pending_len:[u8;MAX_FFA_PLAYERS as usize],
+    pub shots:[u8;MAX_FFA_PLAYERS as usize],
+    pub hits:[u8;MAX_FFA_PLAYERS as usize],
+    pub board_states:[[u8;256];MAX_FFA_PLAYERS as usize],
+    pub reveals:[u8;MAX_FFA_PLAYERS as usize],
+    pub reveal_deadline:u64,
 }
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub struct Init{
@@ -47,37 +179,154 @@ pub struct Init{
     pub gameseed:String,
     pub lamports:u64,
     pub initializer_game_hash:[u8;32],
+    pub initializer_board_root:[u8;32],
+    pub no_touching:u8,
+    pub fleet:Vec<u8>,
+    pub board_width:u8,
+    pub board_height:u8,
+    pub salvo:u8,
+    pub initializer_ship_hashes:Vec<[u8;32]>,
+    pub move_timeout:u64,
+    pub time_unit:u8,
+    pub time_bank:u64,
+    pub increment:u64,
+    pub invited_guest:[u8;32],
+    pub join_key:[u8;32],
+    pub log_moves:u8,
+    pub log_bump:u8,
+    pub best_of:u8,
+    pub special_weapons:u8,
+    pub close_on_settle:u8, //1 logs the result and closes, 0 keeps the whole GameState (1763 bytes on a classic game) for a rematch
+    pub expires_at:u64,
+    pub mint:[u8;32],
+    pub vault_bump:u8,
+    pub escrow_bump:u8,
+    pub host_fee_bps:u16,
     pub bump:u8
 }
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub struct Join{
     pub seed:String,
     pub guest_game_hash:[u8;32],
+    pub guest_board_root:[u8;32],
+    pub guest_ship_hashes:Vec<[u8;32]>,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Rematch{
+    pub lamports:u64,
+    pub game_hash:[u8;32],
+    pub board_root:[u8;32],
+    pub ship_hashes:Vec<[u8;32]>,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct SeriesGame{
+    pub game_hash:[u8;32],
+    pub board_root:[u8;32],
+    pub ship_hashes:Vec<[u8;32]>,
 }
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub struct Move{
     pub mymove:u8,
     pub opponents_last_move_is_hit:u8,
+    pub salt:[u8;32],
+    pub proof:Vec<[u8;32]>,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Special{
+    pub weapon:u8,
+    pub mymove:Move,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct SpecialRecord{
+    pub owner:u8,
+    pub weapon:u8,
+    pub cell:u8,
+    pub answer:u8,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Salvo{
+    pub targets:Vec<u8>,
+    pub opponents_last_salvo_hits:Vec<u8>,
+    pub ships_sunk:u8,
+    pub salts:Vec<[u8;32]>,
+    pub proofs:Vec<Vec<[u8;32]>>,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct FinalReport{
+    pub hits:Vec<u8>,
+    pub salts:Vec<[u8;32]>,
+    pub proofs:Vec<Vec<[u8;32]>>,
 }
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub struct ClaimVictory{
     pub claims:u8,
     pub seed:String,
-    pub s1ax:u8,
-    pub s1cox:u8,
-    pub s1coy:u8,
-    pub s2ax:u8,
-    pub s2cox:u8,
-    pub s2coy:u8,
-    pub s3ax:u8,
-    pub s3cox:u8,
-    pub s3coy:u8,
-    pub s4ax:u8,
-    pub s4cox:u8,
-    pub s4coy:u8,
-    pub s5ax:u8,
-    pub s5cox:u8,
-    pub s5coy:u8,
+    pub fleet:Fleet,
+    pub ship_salts:Vec<[u8;32]>,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ShipPlacement{
+    pub length:u8,
+    pub axis:u8,
+    pub x:u8,
+    pub y:u8,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Fleet{
+    pub ships:Vec<ShipPlacement>,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct SunkShip{
+    pub owner:u8,
+    pub ship:u8,
+    pub placement:ShipPlacement,
+    pub salt:[u8;32],
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct MoveRecord{
+    pub shooter:u8,
+    pub weapon:u8,
+    pub cell:u8,
+    pub result:u8, //0 shot fired, 1 reported miss, 2 reported hit, the raw answer for a special weapon
+    pub timestamp:u64,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct MoveLog{
+    pub game:[u8;32],
+    pub records:Vec<MoveRecord>,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct FfaInit{
+    pub gameseed:String,
+    pub lamports:u64,
+    pub host_fee_bps:u16,
+    pub player_count:u8,
+    pub game_hash:[u8;32],
+    pub board_root:[u8;32],
+    pub no_touching:u8,
+    pub fleet:Vec<u8>,
+    pub board_width:u8,
+    pub board_height:u8,
+    pub move_timeout:u64,
+    pub escrow_bump:u8,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct FfaJoin{
+    pub game_hash:[u8;32],
+    pub board_root:[u8;32],
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct FfaShot{
+    pub target:u8,
+    pub cell:u8,
+    pub hits:Vec<u8>,
+    pub salts:Vec<[u8;32]>,
+    pub proofs:Vec<Vec<[u8;32]>>,
+}
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct FfaClaim{
+    pub seed:String,
+    pub fleet:Fleet,
 }
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub struct Tournament{
@@ -107,6 +356,8 @@ pub struct TournamentAccount{
     pub opponent_played_on:u64,
     pub is_playing:u8,
     pub waiting_opponent_to_join:u8,
+    pub referrer:[u8;32],
+    pub referral_share_bps:u16,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
@@ -132,6 +383,54 @@ pub struct FinderFinder{
     pub tournament_id:String,
 }
 
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProgramConfig{
+    pub min_timeout_seconds:u64,
+    pub max_timeout_seconds:u64,
+    pub min_timeout_slots:u64,
+    pub max_timeout_slots:u64,
+    pub min_host_fee_bps:u16,
+    pub max_host_fee_bps:u16,
+    pub referral_share_bps:u16,
+    pub max_time_bank_seconds:u64,
+    pub max_time_bank_slots:u64,
+    pub max_increment_seconds:u64,
+    pub max_increment_slots:u64,
+    pub bump:u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct HostProfile{
+    pub operator:[u8;32],
+    pub payout:[u8;32],
+    pub fee_bps:u16,
+    pub enabled:u8,
+    pub games_hosted:u64,
+    pub fees_earned:u64, //lamport wagers only, token fees are paid straight to the host's token account
+    pub bump:u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RegisterHost{
+    pub payout:[u8;32],
+    pub fee_bps:u16,
+    pub enabled:u8,
+    pub bump:u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ReferrerProfile{
+    pub referrer:[u8;32],
+    pub games_referred:u64,
+    pub earned:u64,
+    pub bump:u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RegisterReferrer{
+    pub bump:u8,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub struct InitTournamentCounter{
     pub counter_no:u16,
@@ -141,6 +440,16 @@ pub struct InitTournamentCounter{
     pub rent:u64
 }
 
+//leaves of the board merkle tree are indexed by cell, 2^8 covers the [u8;256] board arrays
+pub const BOARD_TREE_DEPTH: usize = 8;
+//proven cell reports that fit one transaction, a larger pending salvo is reported in batches through Finalize
+pub const MAX_PROVEN_REPORTS: usize = 2;
+
+//cell = x + y*width with x,y starting at 1, a zero Init dimension picks the classic board
+pub const CLASSIC_BOARD_SIZE: u8 = 10;
+pub const MIN_BOARD_SIZE: u8 = 5;
+pub const MAX_BOARD_SIZE: u8 = 15;
+
 /*
 10   11   12   13   14   15   16   17   18   19
 20   21   22   23   24   25   26   27   28   29
@@ -154,6 +463,40 @@ pub struct InitTournamentCounter{
 100  101  102  103  104  105  106  107  108  109
 */
 
+//ship lengths in reveal order, an empty Init.fleet picks this one
+pub const CLASSIC_FLEET: [u8;5] = [5,4,3,3,2];
+pub const MAX_FLEET_SHIPS: usize = 10;
+
+//GameResult.reason, the winner is 1 initializer, 2 guest, 3 draw, 0 nobody
+pub const RESULT_SCORE: u8 = 1;
+pub const RESULT_TIME: u8 = 2;
+pub const RESULT_FLAG_FALL: u8 = 3;
+pub const RESULT_RESIGN: u8 = 4;
+pub const RESULT_DRAW_AGREED: u8 = 5;
+pub const RESULT_SERIES_FORFEIT: u8 = 6;
+pub const RESULT_ABORT: u8 = 7;
+pub const RESULT_FALSE_REPORT: u8 = 8;
+pub const RESULT_REVEAL_TIMEOUT: u8 = 9;
+pub const RESULT_EXPIRED: u8 = 10;
+pub const RESULT_SERIES_SCORE: u8 = 11;
+
+//lamports paid out of the rent to whoever closes an expired open game
+pub const EXPIRY_CRANK_BOUNTY: u64 = 10_000;
+
+//special weapons, the answer to one rides in the next move's opponents_last_move_is_hit
+pub const WEAPON_SHOT: u8 = 0;
+pub const WEAPON_RADAR: u8 = 1; //ship cells in the 3x3 area around the cell
+pub const WEAPON_TORPEDO: u8 = 2; //cells travelled east along the row to the first ship cell, 0 if none
+pub const WEAPON_AIRSTRIKE: u8 = 3; //hit bits for center, west, east, north, south
+//specials each player may use in one game, both players' answers fit GameState.special_log
+pub const MAX_SPECIAL_WEAPONS: u8 = 4;
+
+//free-for-all seats, a seat is an index into FfaGameState.players
+pub const MIN_FFA_PLAYERS: u8 = 3;
+pub const MAX_FFA_PLAYERS: u8 = 4;
+//a seat is shot at most once by each other seat before its next turn clears the reports
+pub const FFA_PENDING_CAPACITY: usize = MAX_FFA_PLAYERS as usize - 1;
+
 /*
 1	Carrier	5
 2	Battleship	4